@@ -0,0 +1,16 @@
+//! Check the WFC rule set for orphan sockets, unreachable models and
+//! asymmetric connections, and print each layer's weights.
+//!
+//! cargo run --bin analyze_rules
+//!
+//! Exits with an error when an issue is found, so it can run in CI.
+use std::process::ExitCode;
+
+use chapter4::map::{analysis::analyze, rules::build_rule_set};
+
+fn main() -> ExitCode {
+    let report = analyze(&build_rule_set());
+    println!("{report}");
+
+    if report.is_consistent() { ExitCode::SUCCESS } else { ExitCode::FAILURE }
+}
@@ -0,0 +1,36 @@
+//! Render a generated map to a PNG without opening a window.
+//!
+//! cargo run --bin map_preview -- [--seed N] [--out map.png] [--collision]
+use std::path::PathBuf;
+
+use chapter4::map::preview::{collision_map_for, generate_world, load_tilemap_definition, render_world};
+
+const ATLAS_DEFINITION: &str = "src/assets/tile_layers/tilemap.atlas.ron";
+const ATLAS_IMAGE: &str = "src/assets/tile_layers/tilemap.png";
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut seed = 0;
+    let mut out = PathBuf::from("map_preview.png");
+    let mut collision = false;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--seed" => seed = args.next().ok_or("--seed needs a value")?.parse()?,
+            "--out" => out = args.next().ok_or("--out needs a path")?.into(),
+            "--collision" => collision = true,
+            other => return Err(format!("unknown argument '{other}'").into()),
+        }
+    }
+
+    let tilemap = load_tilemap_definition(ATLAS_DEFINITION.as_ref())?;
+    let atlas = image::open(ATLAS_IMAGE)?.to_rgba8();
+
+    let world = generate_world(seed).map_err(|error| format!("generation failed for seed {seed}: {error}"))?;
+    let collision_map = collision.then(|| collision_map_for(&world));
+
+    render_world(&world, &tilemap, &atlas, collision_map.as_ref())?.save(&out)?;
+    println!("Seed {seed} -> {}", out.display());
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+//! Generate a batch of maps without a window and measure them: tile types,
+//! walkable share, water bodies, largest walkable region, generation time
+//! and failure rate.
+//!
+//! cargo run --release --bin map_stats -- [--maps 100] [--seed 0] [--label name] [--format csv|json] [--out file]
+//!
+//! Run it before and after tuning the rules (e.g. `WATER_WEIGHT` in
+//! `map/rules.rs`) with different labels to compare the two revisions.
+use std::path::PathBuf;
+
+use chapter4::map::stats::{batch_to_csv, measure_batch};
+
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let mut maps = 100;
+    let mut seed = 0;
+    let mut label = String::from("current");
+    let mut json = false;
+    let mut out: Option<PathBuf> = None;
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--maps" => maps = args.next().ok_or("--maps needs a value")?.parse()?,
+            "--seed" => seed = args.next().ok_or("--seed needs a value")?.parse()?,
+            "--label" => label = args.next().ok_or("--label needs a value")?,
+            "--format" => match args.next().as_deref() {
+                Some("csv") => json = false,
+                Some("json") => json = true,
+                _ => return Err("--format is csv or json".into()),
+            },
+            "--out" => out = Some(args.next().ok_or("--out needs a path")?.into()),
+            other => return Err(format!("unknown argument '{other}'").into()),
+        }
+    }
+    if label.contains([',', '"', '\n']) {
+        return Err("--label can't contain commas, quotes or newlines (it's a CSV column)".into());
+    }
+
+    let report = measure_batch(&label, seed, maps);
+    let output = if json { serde_json::to_string_pretty(&report)? } else { batch_to_csv(&report) };
+
+    match &out {
+        Some(path) => std::fs::write(path, output)?,
+        None => print!("{output}"),
+    }
+
+    // The summary goes to stderr so it doesn't end up in piped output
+    let summary = &report.summary;
+    eprintln!(
+        "{}: {} maps from seed {seed}, {} failed ({:.1}%), {:.1} ms per map",
+        summary.label,
+        summary.maps,
+        summary.failures,
+        summary.failure_rate * 100.0,
+        summary.mean_generation_ms
+    );
+    eprintln!(
+        "  walkable {:.1}%, {:.2} water bodies, largest walkable region {:.1} cells",
+        summary.mean_walkable_percent, summary.mean_water_bodies, summary.mean_largest_walkable_region
+    );
+
+    Ok(())
+}
@@ -20,6 +20,7 @@ pub struct AnimationTimer(pub Timer);
 pub struct AnimationClip {
     first: usize,
     last: usize,
+    flip_x: bool,
 }
 
 impl AnimationClip {
@@ -28,13 +29,24 @@ impl AnimationClip {
         Self {
             first,
             last: first + frame_count - 1,
+            flip_x: false,
         }
     }
     
+    /// Draw this clip horizontally flipped (mirrored direction).
+    pub fn flipped(mut self, flip_x: bool) -> Self {
+        self.flip_x = flip_x;
+        self
+    }
+    
     pub fn start(self) -> usize {
         self.first
     }
     
+    pub fn flip_x(self) -> bool {
+        self.flip_x
+    }
+    
     // Check if a frame index belongs to this clip
     pub fn contains(self, index: usize) -> bool {
         (self.first..=self.last).contains(&index)
@@ -48,11 +60,6 @@ impl AnimationClip {
             index + 1
         }
     }
-    
-    // Check if animation has completed (used for non-looping animations like Jump)
-    pub fn is_complete(self, current_index: usize, timer_finished: bool) -> bool {
-        current_index >= self.last && timer_finished
-    }
 }
 
 impl AnimationController {
@@ -61,13 +68,9 @@ impl AnimationController {
     pub fn get_clip(&self, config: &CharacterEntry, facing: Facing) -> Option<AnimationClip> {
         let def = config.animations.get(&self.current_animation)?;
         
-        let row = if def.directional {
-            def.start_row + facing.direction_index()
-        } else {
-            def.start_row
-        };
+        let (row, flip_x) = def.row_for(facing);
         
-        Some(AnimationClip::new(row, def.frame_count, config.atlas_columns))
+        Some(AnimationClip::new(row, def.frame_count, config.atlas_columns).flipped(flip_x))
     }
 }
 
@@ -106,21 +109,25 @@ pub fn animations_playback(
     )>,
 ) {
     for (state, facing, controller, mut timer, mut sprite, config) in query.iter_mut() {
+        let Some(clip) = controller.get_clip(config, *facing) else { continue; };
+        
+        // Mirrored directions reuse the opposite side's row, flipped
+        if sprite.flip_x != clip.flip_x() {
+            sprite.flip_x = clip.flip_x();
+        }
+        
         // Don't animate when idle
         if *state == CharacterState::Idle {
             // Ensure idle sprite is at frame 0
-            if let Some(atlas) = sprite.texture_atlas.as_mut() {
-                if let Some(clip) = controller.get_clip(config, *facing) {
-                    if atlas.index != clip.start() {
-                        atlas.index = clip.start();
-                    }
-                }
+            if let Some(atlas) = sprite.texture_atlas.as_mut()
+                && atlas.index != clip.start()
+            {
+                atlas.index = clip.start();
             }
             continue;
         }
         
         let Some(atlas) = sprite.texture_atlas.as_mut() else { continue; };
-        let Some(clip) = controller.get_clip(config, *facing) else { continue; };
         let Some(anim_def) = config.animations.get(&controller.current_animation) else { continue; };
         
         // Safety: If we somehow ended up on a frame outside our clip, reset.
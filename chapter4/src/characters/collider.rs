@@ -1,7 +1,8 @@
 use bevy::prelude::*;
 
-use crate::collision::CollisionMap;
-use crate::characters::physics::Velocity;
+use crate::collision::{CollisionMap, TileMask};
+use crate::characters::physics::{PhysicsPosition, Velocity};
+use crate::characters::jump::JumpArc;
 use crate::config::player::{COLLIDER_RADIUS};
 
 /// A circular collider for collision detection.
@@ -28,32 +29,39 @@ impl Default for Collider {
 impl Collider {
     /// Get the world position of this collider given an entity's transform.
     pub fn world_position(&self, transform: &Transform) -> Vec2 {
-        transform.translation.truncate() + self.offset
+        self.position_at(transform.translation.truncate())
+    }
+
+    /// Get the world position of this collider for an entity at `position`.
+    pub fn position_at(&self, position: Vec2) -> Vec2 {
+        position + self.offset
     }
 }
 
 pub fn validate_movement(
     map: Option<Res<CollisionMap>>,
     time: Res<Time>,
-    mut query: Query<(&Transform, &mut Velocity, &Collider)>,
+    mut query: Query<(&PhysicsPosition, &mut Velocity, &Collider, Option<&JumpArc>)>,
 ) {
     let Some(map) = map else { return };
 
-    for (transform, mut velocity, collider) in query.iter_mut() {
+    for (position, mut velocity, collider, jump) in query.iter_mut() {
         // Skip if not moving
         if !velocity.is_moving() {
             continue;
         }
 
         // Current collider position
-        let current_pos = collider.world_position(transform);
+        let current_pos = collider.position_at(position.current);
         
         // Desired new position based on velocity
         let delta = velocity.0 * time.delta_secs();
         let desired_pos = current_pos + delta;
 
         // Use swept collision to find valid position
-        let valid_pos = map.sweep_circle(current_pos, desired_pos, collider.radius);
+        // Jumping high enough clears low obstacles
+        let mask = jump.map_or(TileMask::BLOCKING, |jump| jump.blocking_mask());
+        let valid_pos = map.sweep_circle(current_pos, desired_pos, collider.radius, mask);
 
         // Calculate what velocity would get us to valid_pos
         let actual_delta = valid_pos - current_pos;
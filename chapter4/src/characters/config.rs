@@ -2,6 +2,7 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use crate::characters::facing::Facing;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, Default)]
 pub enum AnimationType {
@@ -11,12 +12,118 @@ pub enum AnimationType {
     Jump
 }
 
+/// How many facing rows a directional animation has in the sheet.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DirectionRows {
+    /// Up, Left, Down, Right
+    #[default]
+    Four,
+    /// Up, Left, Down, Right, then UpLeft, DownLeft, DownRight, UpRight
+    Eight,
+}
+
+impl DirectionRows {
+    pub fn count(self) -> usize {
+        match self {
+            DirectionRows::Four => 4,
+            DirectionRows::Eight => 8,
+        }
+    }
+}
+
+/// Directions drawn by horizontally flipping the opposite side's rows.
+/// The mirrored rows are left out of the sheet; the remaining rows keep their order.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum Mirror {
+    /// Every direction has its own row
+    #[default]
+    None,
+    /// Right-facing rows are the Left rows flipped
+    RightFromLeft,
+    /// Left-facing rows are the Right rows flipped
+    LeftFromRight,
+}
+
+impl Mirror {
+    /// Check if a facing is drawn by flipping the opposite side.
+    pub fn flips(self, facing: Facing) -> bool {
+        match self {
+            Mirror::None => false,
+            Mirror::RightFromLeft => facing.to_vec2().x > 0.0,
+            Mirror::LeftFromRight => facing.to_vec2().x < 0.0,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AnimationDefinition {
     pub start_row: usize,
     pub frame_count: usize,
     pub frame_time: f32,
-    pub directional: bool, // true = 4 rows (one per direction), false = 1 row
+    pub directional: bool, // true = one row per direction, false = 1 row
+    #[serde(default)]
+    pub direction_rows: DirectionRows, // only used when directional
+    #[serde(default)]
+    pub mirror: Mirror, // only used when directional
+}
+
+impl AnimationDefinition {
+    /// Facings that have their own row in this animation.
+    fn drawn_facings(&self) -> impl Iterator<Item = Facing> + '_ {
+        Facing::ALL.into_iter().filter(|facing| {
+            (self.direction_rows == DirectionRows::Eight || !facing.is_diagonal())
+                && !self.mirror.flips(*facing)
+        })
+    }
+
+    /// Number of sheet rows this animation uses.
+    pub fn row_count(&self) -> usize {
+        if self.directional { self.drawn_facings().count() } else { 1 }
+    }
+
+    /// Sheet row for a facing, and whether it has to be flipped horizontally.
+    pub fn row_for(&self, facing: Facing) -> (usize, bool) {
+        if !self.directional {
+            return (self.start_row, false);
+        }
+
+        let flip_x = self.mirror.flips(facing);
+        let drawn = if flip_x { facing.mirrored_x() } else { facing };
+        let index = drawn.direction_index(self.direction_rows);
+
+        // Mirrored rows aren't in the sheet, so skip over them
+        let skipped = Facing::ALL
+            .into_iter()
+            .filter(|f| self.mirror.flips(*f))
+            .filter(|f| self.direction_rows == DirectionRows::Eight || !f.is_diagonal())
+            .filter(|f| f.direction_index(self.direction_rows) < index)
+            .count();
+
+        (self.start_row + index - skipped, flip_x)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StaminaConfig {
+    pub max: f32,
+    /// Spent every second while running
+    pub drain_per_second: f32,
+    /// Spent once per jump
+    pub jump_cost: f32,
+    /// Seconds after spending stamina before it starts coming back
+    pub regen_delay: f32,
+    pub regen_per_second: f32,
+}
+
+/// An extra spritesheet drawn over the base `texture_path` (hair, armour, weapon...).
+/// Must use the same grid as the base sheet so frames line up.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CharacterLayer {
+    pub name: String,
+    pub texture_path: String,
+    /// Sheets this layer can be swapped between at runtime (`V` cycles them)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub variants: Vec<String>,
 }
 
 #[derive(Component, Asset, TypePath, Debug, Clone, Serialize, Deserialize)]
@@ -25,17 +132,42 @@ pub struct CharacterEntry {
     pub max_health: f32,
     pub base_move_speed: f32,
     pub run_speed_multiplier: f32,
+    /// How quickly velocity ramps up towards the input speed (units/s²)
+    pub acceleration: f32,
+    /// How quickly velocity bleeds off without input (units/s²)
+    pub deceleration: f32,
+    /// Cap on input-driven speed; knockback can exceed it and decays back
+    pub max_speed: f32,
+    /// Peak height of a jump arc in world units
+    pub jump_height: f32,
+    /// Keep the run-up velocity while in the air
+    pub jump_carry_momentum: bool,
+    pub stamina: StaminaConfig,
+    /// Face diagonals too (sheets without diagonal rows fall back to side rows)
+    #[serde(default)]
+    pub eight_way_facing: bool,
+    /// Base sheet (the body); `layers` are drawn on top of it in order
     pub texture_path: String,
+    #[serde(default)]
+    pub layers: Vec<CharacterLayer>,
     pub tile_size: u32,
     pub atlas_columns: usize,
     pub animations: HashMap<AnimationType, AnimationDefinition>,
 }
 
 impl CharacterEntry {
+    /// How long a jump lasts: the length of the Jump animation, so the arc and
+    /// the animation land together.
+    pub fn jump_duration(&self) -> Option<f32> {
+        self.animations
+            .get(&AnimationType::Jump)
+            .map(|def| def.frame_count as f32 * def.frame_time)
+    }
+
     pub fn calculate_max_animation_row(&self) -> usize {
         self.animations
             .values()
-            .map(|def| if def.directional { def.start_row + 3 } else { def.start_row })
+            .map(|def| def.start_row + def.row_count() - 1)
             .max()
             .unwrap_or(0)
     }
@@ -44,4 +176,58 @@ impl CharacterEntry {
 #[derive(Asset, TypePath, Debug, Clone, Serialize, Deserialize)]
 pub struct CharactersList {
     pub characters: Vec<CharacterEntry>,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn animation(direction_rows: DirectionRows, mirror: Mirror) -> AnimationDefinition {
+        AnimationDefinition {
+            start_row: 10,
+            frame_count: 4,
+            frame_time: 0.1,
+            directional: true,
+            direction_rows,
+            mirror,
+        }
+    }
+
+    #[test]
+    fn row_for_every_layout_and_facing() {
+        // Rows relative to `start_row`, in `Facing::ALL` order:
+        // Up, Left, Down, Right, UpLeft, DownLeft, DownRight, UpRight
+        let cases = [
+            (DirectionRows::Four, Mirror::None, 4, [(0, false), (1, false), (2, false), (3, false), (1, false), (1, false), (3, false), (3, false)]),
+            (DirectionRows::Four, Mirror::RightFromLeft, 3, [(0, false), (1, false), (2, false), (1, true), (1, false), (1, false), (1, true), (1, true)]),
+            (DirectionRows::Four, Mirror::LeftFromRight, 3, [(0, false), (2, true), (1, false), (2, false), (2, true), (2, true), (2, false), (2, false)]),
+            (DirectionRows::Eight, Mirror::None, 8, [(0, false), (1, false), (2, false), (3, false), (4, false), (5, false), (6, false), (7, false)]),
+            (DirectionRows::Eight, Mirror::RightFromLeft, 5, [(0, false), (1, false), (2, false), (1, true), (3, false), (4, false), (4, true), (3, true)]),
+            (DirectionRows::Eight, Mirror::LeftFromRight, 5, [(0, false), (2, true), (1, false), (2, false), (4, true), (3, true), (3, false), (4, false)]),
+        ];
+
+        for (direction_rows, mirror, row_count, rows) in cases {
+            let animation = animation(direction_rows, mirror);
+            assert_eq!(animation.row_count(), row_count, "{direction_rows:?} {mirror:?}");
+            for (facing, (row, flip_x)) in Facing::ALL.into_iter().zip(rows) {
+                assert_eq!(
+                    animation.row_for(facing),
+                    (10 + row, flip_x),
+                    "{direction_rows:?} {mirror:?} {facing:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn non_directional_animations_use_one_unflipped_row() {
+        let animation = AnimationDefinition {
+            directional: false,
+            ..animation(DirectionRows::Eight, Mirror::RightFromLeft)
+        };
+
+        assert_eq!(animation.row_count(), 1);
+        for facing in Facing::ALL {
+            assert_eq!(animation.row_for(facing), (10, false));
+        }
+    }
+}
@@ -1,4 +1,11 @@
 use bevy::prelude::*;
+use std::f32::consts::{FRAC_1_SQRT_2, FRAC_PI_2, FRAC_PI_4};
+
+use crate::characters::config::DirectionRows;
+
+/// Extra angle (radians) input has to move past a sector edge before the facing changes.
+/// Stops flicker between two directions when moving exactly diagonally.
+const FACING_HYSTERESIS: f32 = 0.2;
 
 /// The direction a character is facing.
 /// Separate from movement - character can face one way while moving another.
@@ -9,9 +16,26 @@ pub enum Facing {
     #[default]
     Down,
     Right,
+    UpLeft,
+    DownLeft,
+    DownRight,
+    UpRight,
 }
 
 impl Facing {
+    /// Every facing, in sheet row order.
+    pub const ALL: [Facing; 8] = [
+        Facing::Up,
+        Facing::Left,
+        Facing::Down,
+        Facing::Right,
+        Facing::UpLeft,
+        Facing::DownLeft,
+        Facing::DownRight,
+        Facing::UpRight,
+    ];
+
+    /// Nearest of the four cardinal directions.
     pub fn from_velocity(velocity: Vec2) -> Self {
         if velocity.x.abs() > velocity.y.abs() {
             if velocity.x > 0.0 { Facing::Right } else { Facing::Left }
@@ -19,14 +43,180 @@ impl Facing {
             if velocity.y > 0.0 { Facing::Up } else { Facing::Down }
         }
     }
-    
-    /// Helper to map direction to row offset (0, 1, 2, 3)
-    pub(crate) fn direction_index(self) -> usize {
+
+    /// Nearest of all eight directions.
+    pub fn from_velocity_8(velocity: Vec2) -> Self {
+        // Counter-clockwise from +X in 45° steps
+        const SECTORS: [Facing; 8] = [
+            Facing::Right,
+            Facing::UpRight,
+            Facing::Up,
+            Facing::UpLeft,
+            Facing::Left,
+            Facing::DownLeft,
+            Facing::Down,
+            Facing::DownRight,
+        ];
+        let sector = (velocity.to_angle() / FRAC_PI_4).round() as i32;
+        SECTORS[sector.rem_euclid(8) as usize]
+    }
+
+    /// Pick a new facing for `direction`, sticking with `self` until the input
+    /// clearly leaves its sector.
+    pub fn turn_towards(self, direction: Vec2, eight_way: bool) -> Self {
+        if direction == Vec2::ZERO {
+            return self;
+        }
+
+        let half_sector = if eight_way { FRAC_PI_4 / 2.0 } else { FRAC_PI_2 / 2.0 };
+        let current_allowed = eight_way || !self.is_diagonal();
+        if current_allowed && self.to_vec2().angle_to(direction).abs() <= half_sector + FACING_HYSTERESIS {
+            return self;
+        }
+
+        if eight_way {
+            Facing::from_velocity_8(direction)
+        } else {
+            Facing::from_velocity(direction)
+        }
+    }
+
+    pub fn is_diagonal(self) -> bool {
+        matches!(self, Facing::UpLeft | Facing::DownLeft | Facing::DownRight | Facing::UpRight)
+    }
+
+    /// Unit vector pointing in the facing direction.
+    pub fn to_vec2(self) -> Vec2 {
+        match self {
+            Facing::Up => Vec2::Y,
+            Facing::Left => Vec2::NEG_X,
+            Facing::Down => Vec2::NEG_Y,
+            Facing::Right => Vec2::X,
+            Facing::UpLeft => Vec2::new(-FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+            Facing::DownLeft => Vec2::new(-FRAC_1_SQRT_2, -FRAC_1_SQRT_2),
+            Facing::DownRight => Vec2::new(FRAC_1_SQRT_2, -FRAC_1_SQRT_2),
+            Facing::UpRight => Vec2::new(FRAC_1_SQRT_2, FRAC_1_SQRT_2),
+        }
+    }
+
+    /// The same facing reflected across the vertical axis (Left <-> Right).
+    pub fn mirrored_x(self) -> Self {
+        match self {
+            Facing::Left => Facing::Right,
+            Facing::Right => Facing::Left,
+            Facing::UpLeft => Facing::UpRight,
+            Facing::UpRight => Facing::UpLeft,
+            Facing::DownLeft => Facing::DownRight,
+            Facing::DownRight => Facing::DownLeft,
+            vertical => vertical,
+        }
+    }
+
+    /// Cardinal direction to show when a sheet has no diagonal rows.
+    /// Diagonals use their side view, which reads best while moving.
+    pub fn cardinal(self) -> Self {
         match self {
-            Facing::Up => 0,
-            Facing::Left => 1,
-            Facing::Down => 2,
-            Facing::Right => 3,
+            Facing::UpLeft | Facing::DownLeft => Facing::Left,
+            Facing::UpRight | Facing::DownRight => Facing::Right,
+            cardinal => cardinal,
         }
     }
-}
\ No newline at end of file
+
+    /// Helper to map direction to row offset.
+    /// Four rows: Up, Left, Down, Right (0-3).
+    /// Eight rows: the same four, then UpLeft, DownLeft, DownRight, UpRight (4-7).
+    pub(crate) fn direction_index(self, rows: DirectionRows) -> usize {
+        match (self, rows) {
+            (Facing::Up, _) => 0,
+            (Facing::Left, _) => 1,
+            (Facing::Down, _) => 2,
+            (Facing::Right, _) => 3,
+            (Facing::UpLeft, DirectionRows::Eight) => 4,
+            (Facing::DownLeft, DirectionRows::Eight) => 5,
+            (Facing::DownRight, DirectionRows::Eight) => 6,
+            (Facing::UpRight, DirectionRows::Eight) => 7,
+            (diagonal, DirectionRows::Four) => diagonal.cardinal().direction_index(rows),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at_degrees(degrees: f32) -> Vec2 {
+        Vec2::from_angle(degrees.to_radians())
+    }
+
+    #[test]
+    fn eight_way_sectors_split_halfway_between_directions() {
+        for facing in Facing::ALL {
+            assert_eq!(Facing::from_velocity_8(facing.to_vec2() * 3.0), facing);
+        }
+        // Counter-clockwise from +X, each sector edge lies 22.5° past its direction
+        let ring = [
+            Facing::Right,
+            Facing::UpRight,
+            Facing::Up,
+            Facing::UpLeft,
+            Facing::Left,
+            Facing::DownLeft,
+            Facing::Down,
+            Facing::DownRight,
+        ];
+        for (i, &facing) in ring.iter().enumerate() {
+            let edge = 22.5 + 45.0 * i as f32;
+            let next = ring[(i + 1) % ring.len()];
+            assert_eq!(Facing::from_velocity_8(at_degrees(edge - 0.5)), facing, "below {edge}°");
+            assert_eq!(Facing::from_velocity_8(at_degrees(edge + 0.5)), next, "above {edge}°");
+        }
+    }
+
+    #[test]
+    fn facing_holds_until_input_clears_the_hysteresis_band() {
+        let edge = 45.0 + FACING_HYSTERESIS.to_degrees();
+
+        assert_eq!(Facing::Right.turn_towards(at_degrees(edge - 1.0), false), Facing::Right);
+        assert_eq!(Facing::Right.turn_towards(at_degrees(edge + 1.0), false), Facing::Up);
+        assert_eq!(Facing::Up.turn_towards(at_degrees(90.0 - edge + 1.0), false), Facing::Up);
+        assert_eq!(Facing::Up.turn_towards(at_degrees(90.0 - edge - 1.0), false), Facing::Right);
+
+        let edge = 22.5 + FACING_HYSTERESIS.to_degrees();
+        assert_eq!(Facing::Right.turn_towards(at_degrees(edge - 1.0), true), Facing::Right);
+        assert_eq!(Facing::Right.turn_towards(at_degrees(edge + 1.0), true), Facing::UpRight);
+    }
+
+    #[test]
+    fn wobbling_across_a_sector_edge_does_not_flicker() {
+        for eight_way in [false, true] {
+            let sector_edge = if eight_way { 22.5 } else { 45.0 };
+            let mut facing = Facing::Right;
+            for step in 0..20 {
+                let wobble = if step % 2 == 0 { 3.0 } else { -3.0 };
+                facing = facing.turn_towards(at_degrees(sector_edge + wobble), eight_way);
+                assert_eq!(facing, Facing::Right, "eight_way: {eight_way}, step {step}");
+            }
+        }
+    }
+
+    #[test]
+    fn switching_between_four_and_eight_way() {
+        // A diagonal facing is not allowed in 4-way mode, so it snaps to a cardinal at once
+        let diagonal = at_degrees(50.0);
+        assert_eq!(Facing::UpRight.turn_towards(diagonal, true), Facing::UpRight);
+        assert_eq!(Facing::UpRight.turn_towards(diagonal, false), Facing::Up);
+        assert_eq!(Facing::DownLeft.turn_towards(at_degrees(-170.0), false), Facing::Left);
+
+        // A cardinal facing keeps its narrower 8-way band when switching back
+        assert_eq!(Facing::Up.turn_towards(diagonal, true), Facing::UpRight);
+        assert_eq!(Facing::Up.turn_towards(at_degrees(70.0), true), Facing::Up);
+    }
+
+    #[test]
+    fn no_input_keeps_the_current_facing() {
+        for facing in Facing::ALL {
+            assert_eq!(facing.turn_towards(Vec2::ZERO, false), facing);
+            assert_eq!(facing.turn_towards(Vec2::ZERO, true), facing);
+        }
+    }
+}
@@ -1,10 +1,10 @@
 use bevy::prelude::*;
 use super::{
     state::CharacterState,
-    physics::Velocity,
+    physics::DesiredVelocity,
     facing::Facing,
     config::CharacterEntry,
-    animation::{AnimationController, AnimationTimer},
+    stamina::Stamina,
 };
 
 #[derive(Component)]
@@ -51,23 +51,26 @@ pub fn handle_player_input(
     input: Res<ButtonInput<KeyCode>>,
     mut query: Query<(
         &mut CharacterState,
-        &mut Velocity,
+        &mut DesiredVelocity,
         &mut Facing,
         &CharacterEntry,
+        &Stamina,
     ), With<Player>>,
 ) {
-    let Ok((mut state, mut velocity, mut facing, character)) = query.single_mut() else {
+    let Ok((mut state, mut desired_velocity, mut facing, character, stamina)) = query.single_mut() else {
         return;
     };
     
     // Step 1: Read what keys are pressed
+    // Running and jumping need stamina, an exhausted character falls back to walking
     let direction = read_movement_input(&input);
-    let is_running = input.pressed(KeyCode::ShiftLeft) || input.pressed(KeyCode::ShiftRight);
-    let wants_jump = input.just_pressed(KeyCode::Space);
+    let is_running = (input.pressed(KeyCode::ShiftLeft) || input.pressed(KeyCode::ShiftRight))
+        && stamina.can_run();
+    let wants_jump = input.just_pressed(KeyCode::Space) && stamina.can_jump(&character.stamina);
     
     // Step 2: Update facing direction (which way the character looks)
     if direction != Vec2::ZERO {
-        let new_facing = Facing::from_velocity(direction);
+        let new_facing = facing.turn_towards(direction, character.eight_way_facing);
         if *facing != new_facing {
             *facing = new_facing;
         }
@@ -80,40 +83,8 @@ pub fn handle_player_input(
         *state = new_state;  // This triggers Changed<CharacterState>!
     }
     
-    // Step 4: Calculate velocity based on state
-    // Idle and Jumping = no movement, Walking/Running = movement
-    *velocity = super::physics::calculate_velocity(*state, direction, character);
+    // Step 4: Calculate the velocity we want based on state
+    // Idle = no movement, Walking/Running = movement, Jumping is steered by the jump arc
+    // The physics tick accelerates towards it
+    *desired_velocity = super::physics::calculate_velocity(*state, direction, character);
 }
-
-pub fn update_jump_state(
-    mut query: Query<(
-        &mut CharacterState,
-        &Facing,
-        &AnimationController,
-        &AnimationTimer,
-        &Sprite,
-        &CharacterEntry,
-    ), With<Player>>,
-) {
-    let Ok((mut state, facing, controller, timer, sprite, config)) = query.single_mut() else {
-        return;
-    };
-    
-    // Only check if currently jumping
-    if *state != CharacterState::Jumping {
-        return;
-    }
-    
-    let Some(atlas) = sprite.texture_atlas.as_ref() else {
-        return;
-    };
-    
-    let Some(clip) = controller.get_clip(config, *facing) else {
-        return;
-    };
-    
-    // Check if jump animation has completed
-    if clip.is_complete(atlas.index, timer.just_finished()) {
-        *state = CharacterState::Idle;
-    }
-}
\ No newline at end of file
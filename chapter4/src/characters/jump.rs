@@ -0,0 +1,202 @@
+use bevy::prelude::*;
+
+use crate::collision::{CollisionMap, TileMask};
+use crate::config::player::LOW_OBSTACLE_CLEARANCE;
+use super::{
+    collider::Collider,
+    config::CharacterEntry,
+    physics::{DesiredVelocity, PhysicsPosition, Velocity},
+    state::CharacterState,
+};
+
+/// Jump length used when a character has no Jump animation.
+pub const DEFAULT_JUMP_DURATION: f32 = 0.5;
+
+/// Shadow ellipse radii at ground level (in sprite pixels).
+const SHADOW_RADII: Vec2 = Vec2::new(14.0, 5.0);
+/// Distance from the sprite center down to the feet (in sprite pixels).
+const SHADOW_FEET_OFFSET: f32 = 28.0;
+/// How much the shadow shrinks at the top of the arc.
+const SHADOW_MIN_SCALE: f32 = 0.6;
+
+/// Vertical arc of a jump.
+/// Height is only a visual offset; collision keeps happening on the ground
+/// position, except that low obstacles stop blocking once high enough.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct JumpArc {
+    /// Height above the ground after the last physics tick
+    pub height: f32,
+    /// Height before the last physics tick (for interpolation)
+    pub previous_height: f32,
+    /// Height drawn this frame
+    pub rendered_height: f32,
+    peak: f32,
+    elapsed: f32,
+    duration: f32,
+    /// Ground velocity kept through the jump
+    carry: Vec2,
+    /// Where the jump started, used if there's nowhere to land
+    takeoff: Vec2,
+    active: bool,
+}
+
+impl JumpArc {
+    pub fn is_airborne(&self) -> bool {
+        self.active
+    }
+
+    /// Tiles that block movement at the current height.
+    pub fn blocking_mask(&self) -> TileMask {
+        if self.height > LOW_OBSTACLE_CLEARANCE {
+            TileMask::BLOCKING_AIRBORNE
+        } else {
+            TileMask::BLOCKING
+        }
+    }
+}
+
+/// Marker for the ground shadow drawn under a jumping character.
+#[derive(Component)]
+pub struct JumpShadow;
+
+/// Shadow mesh and material, created once and shared by every character.
+#[derive(Resource)]
+pub struct JumpShadowAssets {
+    mesh: Handle<Mesh>,
+    material: Handle<ColorMaterial>,
+}
+
+pub fn setup_jump_shadow_assets(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    commands.insert_resource(JumpShadowAssets {
+        mesh: meshes.add(Ellipse::new(SHADOW_RADII.x, SHADOW_RADII.y)),
+        material: materials.add(Color::srgba(0.0, 0.0, 0.0, 0.3)),
+    });
+}
+
+/// Spawn the (initially hidden) ground shadow as a child of a character.
+pub fn spawn_jump_shadow(commands: &mut Commands, assets: &JumpShadowAssets, character: Entity) {
+    commands.entity(character).with_child((
+        JumpShadow,
+        Mesh2d(assets.mesh.clone()),
+        MeshMaterial2d(assets.material.clone()),
+        Transform::from_xyz(0.0, -SHADOW_FEET_OFFSET, -0.1),
+        Visibility::Hidden,
+    ));
+}
+
+/// Start the arc when a character enters the Jumping state.
+pub fn start_jumps(
+    mut query: Query<
+        (&CharacterState, &mut JumpArc, &Velocity, &PhysicsPosition, &CharacterEntry),
+        Changed<CharacterState>,
+    >,
+) {
+    for (state, mut arc, velocity, position, character) in query.iter_mut() {
+        if *state != CharacterState::Jumping || arc.active {
+            continue;
+        }
+
+        *arc = JumpArc {
+            peak: character.jump_height,
+            duration: character.jump_duration().unwrap_or(DEFAULT_JUMP_DURATION),
+            carry: if character.jump_carry_momentum { velocity.0 } else { Vec2::ZERO },
+            takeoff: position.current,
+            active: true,
+            ..default()
+        };
+    }
+}
+
+/// Runs in `FixedUpdate` before velocity integration.
+/// Advances the height curve and lands the character when it's done.
+pub fn update_jump_arcs(
+    time: Res<Time>,
+    map: Option<Res<CollisionMap>>,
+    mut query: Query<(
+        &mut JumpArc,
+        &mut CharacterState,
+        &mut DesiredVelocity,
+        &Velocity,
+        &mut PhysicsPosition,
+        &Collider,
+    )>,
+) {
+    let dt = time.delta_secs();
+
+    for (mut arc, mut state, mut desired, velocity, mut position, collider) in query.iter_mut() {
+        arc.previous_height = arc.height;
+        if !arc.active {
+            continue;
+        }
+
+        // Keep the run-up velocity (or none) while in the air
+        desired.0 = arc.carry;
+
+        arc.elapsed += dt;
+        let t = (arc.elapsed / arc.duration).min(1.0);
+
+        // Parabola: zero at takeoff and landing, peak halfway
+        let height = 4.0 * arc.peak * t * (1.0 - t);
+
+        // Coming down onto a low obstacle: hold the height and keep gliding
+        // until there's room to land
+        let landing_blocked = t > 0.5
+            && height <= LOW_OBSTACLE_CLEARANCE
+            && map.as_ref().is_some_and(|map| {
+                !map.is_circle_clear(collider.position_at(position.current), collider.radius, TileMask::BLOCKING)
+            });
+
+        if landing_blocked {
+            if velocity.is_moving() {
+                arc.elapsed -= dt;
+                continue;
+            }
+
+            // Nowhere to go: put the character back where it jumped from
+            position.current = arc.takeoff;
+            position.previous = arc.takeoff;
+            arc.previous_height = 0.0;
+        } else if t < 1.0 {
+            arc.height = height;
+            continue;
+        }
+
+        // Landed
+        arc.height = 0.0;
+        arc.active = false;
+        desired.0 = Vec2::ZERO;
+        *state = CharacterState::Idle;
+    }
+}
+
+/// Keep the shadow on the ground below a jumping character.
+pub fn update_jump_shadows(
+    character_query: Query<(&JumpArc, &Transform), Without<JumpShadow>>,
+    mut shadow_query: Query<(&ChildOf, &mut Transform, &mut Visibility), With<JumpShadow>>,
+) {
+    for (child_of, mut transform, mut visibility) in shadow_query.iter_mut() {
+        let Ok((arc, character_transform)) = character_query.get(child_of.parent()) else {
+            continue;
+        };
+
+        if !arc.is_airborne() {
+            if *visibility != Visibility::Hidden {
+                *visibility = Visibility::Hidden;
+            }
+            continue;
+        }
+
+        // The shadow is a child, so undo the character's scale and jump offset
+        let scale = character_transform.scale.y;
+        transform.translation.y = -SHADOW_FEET_OFFSET - arc.rendered_height / scale;
+
+        let lift = (arc.rendered_height / arc.peak.max(1.0)).clamp(0.0, 1.0);
+        transform.scale = Vec3::splat(1.0 - (1.0 - SHADOW_MIN_SCALE) * lift);
+
+        *visibility = Visibility::Inherited;
+    }
+}
@@ -0,0 +1,217 @@
+use bevy::prelude::*;
+
+use super::config::{CharacterEntry, CharacterLayer};
+use super::input::Player;
+
+/// Z spacing between stacked layers, small enough to stay inside the parent's depth slot.
+const LAYER_Z_STEP: f32 = 0.001;
+
+/// A child sprite drawn on top of the character's base sheet.
+/// It uses the same atlas layout and copies the parent's frame every update.
+#[derive(Component, Debug, Clone)]
+pub struct SpriteLayer {
+    /// Slot name from `CharacterLayer::name` (e.g. "hair", "weapon")
+    pub name: String,
+}
+
+/// Change one layer of a character at runtime.
+/// `texture_path: Some(..)` replaces the layer (or adds it on top if missing),
+/// `None` removes it.
+#[derive(Message, Debug, Clone)]
+pub struct SwapLayer {
+    pub character: Entity,
+    pub layer: String,
+    pub texture_path: Option<String>,
+}
+
+impl SwapLayer {
+    pub fn equip(character: Entity, layer: impl Into<String>, texture_path: impl Into<String>) -> Self {
+        Self {
+            character,
+            layer: layer.into(),
+            texture_path: Some(texture_path.into()),
+        }
+    }
+
+    pub fn remove(character: Entity, layer: impl Into<String>) -> Self {
+        Self {
+            character,
+            layer: layer.into(),
+            texture_path: None,
+        }
+    }
+}
+
+/// Press `V` to swap each of the player's layers to its next variant.
+pub fn cycle_layer_variants(
+    input: Res<ButtonInput<KeyCode>>,
+    query: Query<(Entity, &CharacterEntry), With<Player>>,
+    mut swaps: MessageWriter<SwapLayer>,
+) {
+    if !input.just_pressed(KeyCode::KeyV) {
+        return;
+    }
+    let Ok((entity, entry)) = query.single() else {
+        return;
+    };
+
+    for layer in entry.layers.iter().filter(|layer| !layer.variants.is_empty()) {
+        // A sheet outside the list (or the last one) wraps around to the first variant
+        let next = layer
+            .variants
+            .iter()
+            .position(|path| *path == layer.texture_path)
+            .map_or(0, |index| (index + 1) % layer.variants.len());
+        swaps.write(SwapLayer::equip(entity, &layer.name, &layer.variants[next]));
+    }
+}
+
+/// Apply layer swaps to the character's entry.
+/// The entry is the source of truth; `rebuild_character_layers` picks up the change.
+pub fn apply_layer_swaps(
+    mut swaps: MessageReader<SwapLayer>,
+    mut query: Query<&mut CharacterEntry>,
+) {
+    for swap in swaps.read() {
+        let Ok(mut entry) = query.get_mut(swap.character) else {
+            continue;
+        };
+
+        let existing = entry.layers.iter().position(|layer| layer.name == swap.layer);
+        match (&swap.texture_path, existing) {
+            (Some(path), Some(index)) => entry.layers[index].texture_path = path.clone(),
+            (Some(path), None) => entry.layers.push(CharacterLayer {
+                name: swap.layer.clone(),
+                texture_path: path.clone(),
+                variants: Vec::new(),
+            }),
+            (None, Some(index)) => {
+                entry.layers.remove(index);
+            }
+            (None, None) => {}
+        }
+    }
+}
+
+/// Respawn a character's layer sprites whenever its entry changes
+/// (initialization, character switch, or a layer swap).
+pub fn rebuild_character_layers(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    characters: Query<(Entity, &CharacterEntry, &Sprite, Option<&Children>), Changed<CharacterEntry>>,
+    layers: Query<(), With<SpriteLayer>>,
+) {
+    for (entity, entry, sprite, children) in characters.iter() {
+        // Layers need the base sheet's grid to line up
+        let Some(atlas) = sprite.texture_atlas.as_ref() else {
+            continue;
+        };
+
+        for child in children.into_iter().flatten() {
+            if layers.contains(*child) {
+                commands.entity(*child).despawn();
+            }
+        }
+
+        // Ordered bottom to top, all above the base sheet
+        for (i, layer) in entry.layers.iter().enumerate() {
+            let mut layer_sprite = Sprite::from_atlas_image(
+                asset_server.load(&layer.texture_path),
+                atlas.clone(),
+            );
+            layer_sprite.flip_x = sprite.flip_x;
+
+            commands.entity(entity).with_child((
+                SpriteLayer { name: layer.name.clone() },
+                layer_sprite,
+                Transform::from_xyz(0.0, 0.0, LAYER_Z_STEP * (i + 1) as f32),
+            ));
+        }
+    }
+}
+
+/// Keep every layer on the same frame and mirroring as its character.
+/// Runs after `animations_playback`.
+pub fn sync_layer_frames(
+    characters: Query<&Sprite, Without<SpriteLayer>>,
+    mut layers: Query<(&ChildOf, &mut Sprite), With<SpriteLayer>>,
+) {
+    for (child_of, mut layer_sprite) in layers.iter_mut() {
+        let Ok(sprite) = characters.get(child_of.parent()) else {
+            continue;
+        };
+        let Some(index) = sprite.texture_atlas.as_ref().map(|atlas| atlas.index) else {
+            continue;
+        };
+
+        if layer_sprite.flip_x != sprite.flip_x {
+            layer_sprite.flip_x = sprite.flip_x;
+        }
+        if let Some(atlas) = layer_sprite.texture_atlas.as_mut()
+            && atlas.index != index
+        {
+            atlas.index = index;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::ron;
+    use bevy::ecs::message::Messages;
+    use bevy::ecs::system::RunSystemOnce;
+    use std::path::Path;
+
+    use super::*;
+    use crate::characters::config::CharactersList;
+
+    const ASSETS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/assets");
+
+    fn shipped_characters() -> CharactersList {
+        let text = std::fs::read_to_string(Path::new(ASSETS).join("characters/characters.ron")).unwrap();
+        ron::from_str(&text).unwrap()
+    }
+
+    #[test]
+    fn shipped_layers_point_at_real_sheets() {
+        let characters = shipped_characters();
+        let layers: Vec<&CharacterLayer> =
+            characters.characters.iter().flat_map(|entry| &entry.layers).collect();
+
+        assert!(!layers.is_empty(), "at least one character ships with a layer");
+        for layer in layers {
+            for path in std::iter::once(&layer.texture_path).chain(&layer.variants) {
+                assert!(Path::new(ASSETS).join(path).is_file(), "layer '{}' uses missing {path}", layer.name);
+            }
+        }
+    }
+
+    #[test]
+    fn pressing_v_cycles_each_layer_through_its_variants() {
+        let entry = shipped_characters()
+            .characters
+            .into_iter()
+            .find(|entry| entry.layers.iter().any(|layer| layer.variants.len() > 1))
+            .unwrap();
+        let layer = entry.layers.iter().find(|layer| layer.variants.len() > 1).unwrap().clone();
+        let start = layer.variants.iter().position(|path| *path == layer.texture_path).unwrap();
+
+        let mut world = World::new();
+        world.init_resource::<Messages<SwapLayer>>();
+        world.init_resource::<ButtonInput<KeyCode>>();
+        let player = world.spawn((Player, entry)).id();
+
+        for step in 1..=layer.variants.len() {
+            let mut input = world.resource_mut::<ButtonInput<KeyCode>>();
+            input.reset(KeyCode::KeyV);
+            input.press(KeyCode::KeyV);
+            world.run_system_once(cycle_layer_variants).unwrap();
+            world.run_system_once(apply_layer_swaps).unwrap();
+
+            let entry = world.get::<CharacterEntry>(player).unwrap();
+            let current = entry.layers.iter().find(|l| l.name == layer.name).unwrap();
+            let expected = &layer.variants[(start + step) % layer.variants.len()];
+            assert_eq!(&current.texture_path, expected, "after {step} presses");
+        }
+    }
+}
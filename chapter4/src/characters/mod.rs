@@ -6,12 +6,15 @@ pub mod facing;
 pub mod input; 
 pub mod physics;  
 pub mod collider;
-mod rendering;
+pub mod jump;
+pub mod stamina;
+pub mod layers;
 
 use bevy::prelude::*;
 use bevy_common_assets::ron::RonAssetPlugin;
 use config::CharactersList;
 use crate::state::GameState;
+use crate::config::physics::FIXED_TIMESTEP_HZ;
 
 
 pub struct CharactersPlugin;
@@ -19,17 +22,30 @@ pub struct CharactersPlugin;
 impl Plugin for CharactersPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(RonAssetPlugin::<CharactersList>::new(&["characters.ron"]))
+            .add_message::<layers::SwapLayer>()
             .init_resource::<spawn::CurrentCharacterIndex>()
-            .add_systems(Startup, spawn::spawn_player)
+            .insert_resource(Time::<Fixed>::from_hz(FIXED_TIMESTEP_HZ))
+            .add_systems(Startup, (spawn::spawn_player, jump::setup_jump_shadow_assets))
             .add_systems(Update, (
                 input::handle_player_input,
                 spawn::switch_character,
-                input::update_jump_state,
+                jump::start_jumps,
+                stamina::update_stamina,
                 animation::on_state_change_update_animation,
+                physics::interpolate_transforms,
+                jump::update_jump_shadows,
+                animation::animations_playback,
+                layers::cycle_layer_variants,
+                layers::apply_layer_swaps,
+                layers::rebuild_character_layers,
+                layers::sync_layer_frames,
+            ).chain().run_if(in_state(GameState::Playing)))
+            // Movement and collision run at a fixed rate for frame-rate independent results
+            .add_systems(FixedUpdate, (
+                jump::update_jump_arcs,
+                physics::integrate_velocity,
                 collider::validate_movement,
                 physics::apply_velocity,
-                rendering::update_player_depth,
-                animation::animations_playback,
             ).chain().run_if(in_state(GameState::Playing)));
     }
 }
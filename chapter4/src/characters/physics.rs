@@ -1,5 +1,5 @@
 use bevy::prelude::*;
-use super::{state::CharacterState, config::CharacterEntry};
+use super::{state::CharacterState, config::CharacterEntry, jump::JumpArc};
 
 /// Linear velocity in world units per second.
 /// Systems that want to move an entity modify this.
@@ -15,31 +15,143 @@ impl Velocity {
     }
 }
 
+/// Velocity the character is trying to reach, set from input.
+/// `Velocity` moves towards it using the character's acceleration and deceleration.
+#[derive(Component, Debug, Clone, Copy, Default, Deref, DerefMut)]
+pub struct DesiredVelocity(pub Vec2);
+
+/// One-shot change in velocity (knockback, dashes), consumed on the next physics tick.
+#[derive(Component, Debug, Clone, Copy, Default, Deref, DerefMut)]
+pub struct Impulse(pub Vec2);
+
+impl Impulse {
+    /// Queue an impulse on top of any already waiting for this tick.
+    pub fn add(&mut self, impulse: Vec2) {
+        self.0 += impulse;
+    }
+}
+
+/// Continuous acceleration applied every tick (wind, currents, conveyor tiles).
+#[derive(Component, Debug, Clone, Copy, Default, Deref, DerefMut)]
+pub struct ExternalForce(pub Vec2);
+
+/// Simulated position, only advanced in `FixedUpdate`.
+/// `Transform` is interpolated between `previous` and `current` so rendering
+/// stays smooth no matter how the frame rate lines up with the tick rate.
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct PhysicsPosition {
+    pub previous: Vec2,
+    pub current: Vec2,
+}
+
+impl PhysicsPosition {
+    pub fn new(position: Vec2) -> Self {
+        Self {
+            previous: position,
+            current: position,
+        }
+    }
+}
+
 pub fn calculate_velocity(
     state: CharacterState,
     direction: Vec2,
     character: &CharacterEntry,
-) -> Velocity {
+) -> DesiredVelocity {
     match state {
-        CharacterState::Idle => Velocity::ZERO,
-        CharacterState::Jumping => Velocity::ZERO,  // No movement during jump
+        CharacterState::Idle => DesiredVelocity(Vec2::ZERO),
+        // The jump arc steers the character in the air (see `jump::update_jump_arcs`)
+        CharacterState::Jumping => DesiredVelocity(Vec2::ZERO),
         CharacterState::Walking => {
-            Velocity(direction.normalize_or_zero() * character.base_move_speed)
+            DesiredVelocity(direction.normalize_or_zero() * character.base_move_speed)
         }
         CharacterState::Running => {
-            Velocity(direction.normalize_or_zero() * character.base_move_speed * character.run_speed_multiplier)
+            DesiredVelocity(direction.normalize_or_zero() * character.base_move_speed * character.run_speed_multiplier)
         }
     }
 }
 
+/// Runs in `FixedUpdate` before collision.
+/// Steers velocity towards the desired velocity, then adds forces and impulses.
+/// Only the input side is capped at `max_speed`: knockback may push past it and
+/// is bled off by the same steering over the next ticks.
+pub fn integrate_velocity(
+    time: Res<Time>,
+    mut query: Query<(
+        &mut Velocity,
+        &DesiredVelocity,
+        &mut Impulse,
+        &ExternalForce,
+        &CharacterEntry,
+    )>,
+) {
+    let dt = time.delta_secs();
+
+    for (mut velocity, desired, mut impulse, force, character) in query.iter_mut() {
+        // Speed up while there is input, slow down (friction) when there isn't
+        let rate = if desired.0 != Vec2::ZERO {
+            character.acceleration
+        } else {
+            character.deceleration
+        };
+        let target = desired.0.clamp_length_max(character.max_speed);
+        let mut new_velocity = velocity.0.move_towards(target, rate * dt);
+
+        new_velocity += force.0 * dt;
 
+        if impulse.0 != Vec2::ZERO {
+            new_velocity += impulse.0;
+            impulse.0 = Vec2::ZERO;
+        }
+
+        // Snap tiny velocities to zero so the character actually comes to rest
+        if new_velocity.length_squared() < 0.01 {
+            new_velocity = Vec2::ZERO;
+        }
+
+        if velocity.0 != new_velocity {
+            velocity.0 = new_velocity;
+        }
+    }
+}
+
+
+/// Runs in `FixedUpdate`, so `time` is the fixed clock and every tick has the same delta.
 pub fn apply_velocity(
     time: Res<Time>,
-    mut query: Query<(&Velocity, &mut Transform)>,
+    mut query: Query<(&Velocity, &mut PhysicsPosition)>,
 ) {
-    for (velocity, mut transform) in query.iter_mut() {
+    for (velocity, mut position) in query.iter_mut() {
+        position.previous = position.current;
         if velocity.is_moving() {
-            transform.translation += velocity.0.extend(0.0) * time.delta_secs();
+            position.current += velocity.0 * time.delta_secs();
+        }
+    }
+}
+
+/// Blend the rendered position between the last two physics ticks.
+/// Jump height is drawn as an upward offset on top of the ground position.
+pub fn interpolate_transforms(
+    fixed_time: Res<Time<Fixed>>,
+    mut query: Query<(&PhysicsPosition, &mut Transform, Option<&mut JumpArc>)>,
+) {
+    let alpha = fixed_time.overstep_fraction();
+
+    for (position, mut transform, jump) in query.iter_mut() {
+        let mut rendered = position.previous.lerp(position.current, alpha);
+
+        if let Some(mut jump) = jump {
+            let height = jump.previous_height.lerp(jump.height, alpha);
+            if jump.rendered_height != height {
+                jump.rendered_height = height;
+            }
+            rendered.y += height;
+        }
+
+        // Avoid triggering Changed<Transform> when standing still
+        if transform.translation.truncate() != rendered {
+            transform.translation.x = rendered.x;
+            transform.translation.y = rendered.y;
         }
     }
 }
\ No newline at end of file
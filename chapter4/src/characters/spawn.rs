@@ -1,12 +1,16 @@
+use bevy::ecs::system::SystemParam;
 use bevy::prelude::*;
 use crate::characters::animation::*;
 use crate::characters::config::{CharacterEntry, CharactersList};
 use crate::characters::input::Player;  // Changed from movement::Player
 use crate::characters::state::CharacterState;  // Line update alert
-use crate::characters::physics::Velocity;  // Line update alert
+use crate::characters::physics::{DesiredVelocity, ExternalForce, Impulse, PhysicsPosition, Velocity};  // Line update alert
 use crate::characters::facing::Facing;  // Line update alert
 use crate::characters::collider::Collider; 
+use crate::characters::jump::{JumpArc, JumpShadowAssets, spawn_jump_shadow};
+use crate::characters::stamina::Stamina;
 use crate::config::player::{PLAYER_SCALE, PLAYER_Z_POSITION}; 
+use crate::map::depth::YSort;
 
 
 #[derive(Resource, Default)]
@@ -19,19 +23,39 @@ pub struct CharactersListResource {
     pub handle: Handle<CharactersList>,
 }
 
-fn create_character_atlas_layout(
-    atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
-    character_entry: &CharacterEntry,
-) -> Handle<TextureAtlasLayout> {
-    let max_row = character_entry.calculate_max_animation_row();
-    
-    atlas_layouts.add(TextureAtlasLayout::from_grid(
-        UVec2::splat(character_entry.tile_size),
-        character_entry.atlas_columns as u32,
-        (max_row + 1) as u32,
-        None,
-        None,
-    ))
+/// What it takes to build a character's sprite from its entry.
+#[derive(SystemParam)]
+pub struct CharacterSprites<'w> {
+    asset_server: Res<'w, AssetServer>,
+    atlas_layouts: ResMut<'w, Assets<TextureAtlasLayout>>,
+}
+
+impl CharacterSprites<'_> {
+    /// Load the character's sheet and cut it into a grid big enough for all its animation rows.
+    fn sprite_for(&mut self, character_entry: &CharacterEntry) -> Sprite {
+        let max_row = character_entry.calculate_max_animation_row();
+
+        let layout = self.atlas_layouts.add(TextureAtlasLayout::from_grid(
+            UVec2::splat(character_entry.tile_size),
+            character_entry.atlas_columns as u32,
+            (max_row + 1) as u32,
+            None,
+            None,
+        ));
+
+        Sprite::from_atlas_image(
+            self.asset_server.load(&character_entry.texture_path),
+            TextureAtlas {
+                layout,
+                index: 0,
+            },
+        )
+    }
+}
+
+/// Feet sit at the bottom of the frame, half a (scaled) tile below the center.
+fn feet_offset(character_entry: &CharacterEntry) -> f32 {
+    character_entry.tile_size as f32 * PLAYER_SCALE / 2.0
 }
 
 pub fn spawn_player(
@@ -59,20 +83,23 @@ pub fn spawn_player(
     ));
 }
 
+/// Player entities that haven't received their character components yet.
+type UninitializedPlayer = (With<Player>, Without<AnimationController>);
+
 pub fn initialize_player_character(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    mut sprites: CharacterSprites,
+    shadow_assets: Res<JumpShadowAssets>,
     characters_lists: Res<Assets<CharactersList>>,
     character_index: Res<CurrentCharacterIndex>,
     characters_list_res: Option<Res<CharactersListResource>>,
-    mut query: Query<Entity, (With<Player>, Without<AnimationController>)>,
+    mut query: Query<(Entity, &Transform), UninitializedPlayer>,
 ) {
     let Some(characters_list_res) = characters_list_res else {
         return;
     };
     
-    for entity in query.iter_mut() {
+    for (entity, transform) in query.iter_mut() {
         let Some(characters_list) = characters_lists.get(&characters_list_res.handle) else {
             continue;
         };
@@ -83,27 +110,27 @@ pub fn initialize_player_character(
         
         let character_entry = &characters_list.characters[character_index.index];
         
-        let texture = asset_server.load(&character_entry.texture_path);
-        let layout = create_character_atlas_layout(&mut atlas_layouts, character_entry);
-        
-        let sprite = Sprite::from_atlas_image(
-            texture,
-            TextureAtlas {
-                layout,
-                index: 0,
-            },
-        );
+        let sprite = sprites.sprite_for(character_entry);
         
         commands.entity(entity).insert((
             AnimationController::default(),
             CharacterState::default(),   // Line update alert
             Velocity::default(),         // Line update alert  
+            DesiredVelocity::default(),
+            Impulse::default(),
+            ExternalForce::default(),
+            PhysicsPosition::new(transform.translation.truncate()),
             Facing::default(),           // Line update alert
             Collider::default(),
+            JumpArc::default(),
+            Stamina::new(&character_entry.stamina),
+            YSort::new(feet_offset(character_entry)),
             AnimationTimer(Timer::from_seconds(DEFAULT_ANIMATION_FRAME_TIME, TimerMode::Repeating)),
             character_entry.clone(),
             sprite,
         ));
+
+        spawn_jump_shadow(&mut commands, &shadow_assets, entity);
     }
 }
 
@@ -115,9 +142,10 @@ pub fn switch_character(
     mut query: Query<(
         &mut CharacterEntry,
         &mut Sprite,
+        &mut Stamina,
+        &mut YSort,
     ), With<Player>>,
-    mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-    asset_server: Res<AssetServer>,
+    mut sprites: CharacterSprites,
 ) {
     // Map digit keys to indices
     const DIGIT_KEYS: [KeyCode; 9] = [
@@ -150,7 +178,7 @@ pub fn switch_character(
     character_index.index = new_index;
     
     // Update player entity
-    let Ok((mut current_entry, mut sprite)) = query.single_mut() else {
+    let Ok((mut current_entry, mut sprite, mut stamina, mut y_sort)) = query.single_mut() else {
         return;
     };
     
@@ -158,16 +186,9 @@ pub fn switch_character(
     
     // Update character entry
     *current_entry = character_entry.clone();
+    *stamina = Stamina::new(&character_entry.stamina);
+    *y_sort = YSort::new(feet_offset(character_entry));
     
     // Update sprite with new texture
-    let texture = asset_server.load(&character_entry.texture_path);
-    let layout = create_character_atlas_layout(&mut atlas_layouts, character_entry);
-    
-    *sprite = Sprite::from_atlas_image(
-        texture,
-        TextureAtlas {
-            layout,
-            index: 0,
-        },
-    );
+    *sprite = sprites.sprite_for(character_entry);
 }
\ No newline at end of file
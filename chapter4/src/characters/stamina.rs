@@ -0,0 +1,76 @@
+use bevy::prelude::*;
+
+use super::{config::{CharacterEntry, StaminaConfig}, state::CharacterState};
+
+/// Fraction of max stamina needed before an exhausted character can run again.
+const EXHAUSTION_RECOVERY: f32 = 0.3;
+
+/// Stamina spent by running and jumping.
+/// Limits come from the character's `StaminaConfig`.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Stamina {
+    pub current: f32,
+    pub max: f32,
+    /// Seconds left before regeneration kicks in
+    pub regen_cooldown: f32,
+    /// Ran dry; can't run until partly recovered
+    pub exhausted: bool,
+}
+
+impl Stamina {
+    pub fn new(config: &StaminaConfig) -> Self {
+        Self {
+            current: config.max,
+            max: config.max,
+            regen_cooldown: 0.0,
+            exhausted: false,
+        }
+    }
+
+    /// Current stamina in [0, 1], for HUD bars.
+    pub fn fraction(&self) -> f32 {
+        if self.max > 0.0 { (self.current / self.max).clamp(0.0, 1.0) } else { 0.0 }
+    }
+
+    pub fn can_run(&self) -> bool {
+        !self.exhausted && self.current > 0.0
+    }
+
+    pub fn can_jump(&self, config: &StaminaConfig) -> bool {
+        self.current >= config.jump_cost
+    }
+
+    fn spend(&mut self, amount: f32, config: &StaminaConfig) {
+        self.current = (self.current - amount).max(0.0);
+        self.regen_cooldown = config.regen_delay;
+        if self.current <= 0.0 {
+            self.exhausted = true;
+        }
+    }
+}
+
+pub fn update_stamina(
+    time: Res<Time>,
+    mut query: Query<(&mut Stamina, Ref<CharacterState>, &CharacterEntry)>,
+) {
+    let dt = time.delta_secs();
+
+    for (mut stamina, state, character) in query.iter_mut() {
+        let config = &character.stamina;
+
+        match *state {
+            CharacterState::Jumping if state.is_changed() => stamina.spend(config.jump_cost, config),
+            CharacterState::Running => stamina.spend(config.drain_per_second * dt, config),
+            _ if stamina.regen_cooldown > 0.0 => {
+                stamina.regen_cooldown = (stamina.regen_cooldown - dt).max(0.0);
+            }
+            _ if stamina.current < stamina.max => {
+                stamina.current = (stamina.current + config.regen_per_second * dt).min(stamina.max);
+                if stamina.exhausted && stamina.fraction() >= EXHAUSTION_RECOVERY {
+                    stamina.exhausted = false;
+                }
+            }
+            _ => {}
+        }
+    }
+}
@@ -1,7 +1,11 @@
 use bevy::prelude::*;
-use super::CollisionMap;
+use super::{CollisionMap, TileMask, TriggerEvent, TriggerShape, TriggerZone};
 use crate::characters::input::Player;
 use crate::characters::collider::Collider;
+use crate::characters::facing::Facing;
+
+/// How far the facing ray is cast in debug view (in world units).
+const DEBUG_RAY_LENGTH: f32 = 160.0;
 
 /// Resource to toggle debug visualization.
 #[derive(Resource, Default)]
@@ -107,4 +111,88 @@ pub fn debug_player_position(
             );
         }
     }
-}
\ No newline at end of file
+}
+
+pub fn debug_facing_ray(
+    player_query: Query<(&Transform, &Collider, &Facing), With<Player>>,
+    map: Option<Res<CollisionMap>>,
+    debug_enabled: Res<DebugCollisionEnabled>,
+    mut gizmos: Gizmos,
+) {
+    if !debug_enabled.0 {
+        return;
+    }
+
+    let Some(map) = map else { return };
+    let Ok((transform, collider, facing)) = player_query.single() else { return };
+
+    let start = collider.world_position(transform);
+    let direction = facing.to_vec2();
+
+    // Cast along the facing direction, stopping at anything that blocks sight
+    match map.raycast(start, direction, DEBUG_RAY_LENGTH, TileMask::SIGHT) {
+        Some(hit) => {
+            gizmos.line_2d(start, hit.point, Color::srgb(1.0, 0.5, 0.0));
+            gizmos.line_2d(hit.point, hit.point + hit.normal * 10.0, Color::srgb(1.0, 0.0, 1.0));
+            gizmos.circle_2d(hit.point, 3.0, Color::srgb(1.0, 0.5, 0.0));
+        }
+        None => {
+            gizmos.line_2d(start, start + direction * DEBUG_RAY_LENGTH, Color::srgba(1.0, 0.5, 0.0, 0.4));
+        }
+    }
+}
+
+pub fn debug_draw_triggers(
+    zone_query: Query<(&TriggerZone, Option<&Transform>)>,
+    map: Option<Res<CollisionMap>>,
+    debug_enabled: Res<DebugCollisionEnabled>,
+    mut gizmos: Gizmos,
+) {
+    if !debug_enabled.0 {
+        return;
+    }
+
+    let color = Color::srgb(0.3, 0.5, 1.0);
+
+    for (zone, transform) in zone_query.iter() {
+        let zone_pos = transform.map_or(Vec2::ZERO, |t| t.translation.truncate());
+
+        match zone.shape {
+            TriggerShape::Rect { half_size } => {
+                gizmos.rect_2d(zone_pos, half_size * 2.0, color);
+            }
+            TriggerShape::Circle { radius } => {
+                gizmos.circle_2d(zone_pos, radius, color);
+            }
+            TriggerShape::Region { min, max } => {
+                let Some(map) = map.as_ref() else { continue };
+                let corner_min = map.grid_to_world(min.x, min.y) - Vec2::splat(map.tile_size() / 2.0);
+                let corner_max = map.grid_to_world(max.x, max.y) + Vec2::splat(map.tile_size() / 2.0);
+                gizmos.rect_2d((corner_min + corner_max) / 2.0, corner_max - corner_min, color);
+            }
+            // Tile zones follow the terrain, the collision overlay already shows it
+            TriggerShape::Tiles(_) => {}
+        }
+    }
+}
+
+pub fn debug_log_triggers(
+    mut trigger_reader: MessageReader<TriggerEvent>,
+    zone_query: Query<&TriggerZone>,
+) {
+    for event in trigger_reader.read() {
+        match *event {
+            TriggerEvent::Enter { zone, entity } => {
+                if let Ok(zone) = zone_query.get(zone) {
+                    info!("{entity} entered '{}'", zone.name);
+                }
+            }
+            TriggerEvent::Exit { zone, entity } => {
+                if let Ok(zone) = zone_query.get(zone) {
+                    info!("{entity} left '{}'", zone.name);
+                }
+            }
+            TriggerEvent::Stay { .. } => {}
+        }
+    }
+}
@@ -1,12 +1,16 @@
 use bevy::prelude::*;
-use super::TileType;
+use super::{TileMask, TileType};
 
 /// Collision map resource that stores walkability information.
 /// Provides efficient spatial queries for movement validation.
+///
+/// Every cell keeps its full layer stack (ground, overlays, props) ordered
+/// bottom to top, so systems can ask both "can I walk here" and
+/// "what ground is under this prop".
 #[derive(Resource)]
 pub struct CollisionMap {
-    /// Flat array of tile types (row-major order)
-    tiles: Vec<TileType>,
+    /// Layer stack per cell, bottom to top (row-major order)
+    cells: Vec<Vec<TileType>>,
     /// Grid dimensions
     width: i32,
     height: i32,
@@ -22,7 +26,7 @@ impl CollisionMap {
     pub fn new(width: i32, height: i32, tile_size: f32, origin_x: f32, origin_y: f32) -> Self {
         let size = (width * height) as usize;
         Self {
-            tiles: vec![TileType::Empty; size],
+            cells: vec![Vec::new(); size],
             width,
             height,
             tile_size,
@@ -57,25 +61,82 @@ impl CollisionMap {
         )
     }
 
+    /// All tiles stacked in a cell, bottom to top.
+    /// Out-of-bounds cells return an empty slice.
+    pub fn tiles_at(&self, x: i32, y: i32) -> &[TileType] {
+        if self.in_bounds(x, y) {
+            &self.cells[self.xy_to_idx(x, y)]
+        } else {
+            &[]
+        }
+    }
+
+    /// Topmost tile in a cell (`Empty` if nothing was placed there).
     pub fn get_tile(&self, x: i32, y: i32) -> Option<TileType> {
         if self.in_bounds(x, y) {
-            Some(self.tiles[self.xy_to_idx(x, y)])
+            Some(self.tiles_at(x, y).last().copied().unwrap_or_default())
         } else {
             None
         }
     }
 
-    /// Set a tile at grid coordinates.
-    pub fn set_tile(&mut self, x: i32, y: i32, tile_type: TileType) {
+    /// Topmost tile in a cell that blocks movement, if any.
+    pub fn top_blocking(&self, x: i32, y: i32) -> Option<TileType> {
+        self.tiles_at(x, y)
+            .iter()
+            .rev()
+            .find(|tile| !tile.is_walkable())
+            .copied()
+    }
+
+    /// Topmost tile in a cell whose type is in `mask`, if any.
+    pub fn top_in_mask(&self, x: i32, y: i32, mask: TileMask) -> Option<TileType> {
+        self.tiles_at(x, y)
+            .iter()
+            .rev()
+            .find(|tile| mask.contains(**tile))
+            .copied()
+    }
+
+    /// Topmost walkable tile in a cell, e.g. the grass under a tree.
+    pub fn ground_at(&self, x: i32, y: i32) -> Option<TileType> {
+        self.tiles_at(x, y)
+            .iter()
+            .rev()
+            .find(|tile| tile.is_walkable())
+            .copied()
+    }
+
+    /// Place a tile on top of the cell's current stack.
+    pub fn push_tile(&mut self, x: i32, y: i32, tile_type: TileType) {
+        if self.in_bounds(x, y) {
+            let idx = self.xy_to_idx(x, y);
+            self.cells[idx].push(tile_type);
+        }
+    }
+
+    /// Remove every tile from a cell.
+    pub fn clear_tiles(&mut self, x: i32, y: i32) {
         if self.in_bounds(x, y) {
             let idx = self.xy_to_idx(x, y);
-            self.tiles[idx] = tile_type;
+            self.cells[idx].clear();
+        }
+    }
+
+    /// Swap every `from` tile in a cell's stack for `to`.
+    pub fn replace_tile(&mut self, x: i32, y: i32, from: TileType, to: TileType) {
+        if self.in_bounds(x, y) {
+            let idx = self.xy_to_idx(x, y);
+            for tile in self.cells[idx].iter_mut().filter(|tile| **tile == from) {
+                *tile = to;
+            }
         }
     }
 
     /// Check if a grid position is walkable.
+    /// A cell is walkable when no layer in its stack blocks movement.
     pub fn is_walkable(&self, x: i32, y: i32) -> bool {
-        self.get_tile(x, y).map_or(false, |t| t.is_walkable())
+        self.in_bounds(x, y) && self.top_blocking(x, y).is_none()
     }
 
     /// Check if a world position is walkable.
@@ -114,7 +175,8 @@ impl CollisionMap {
             && center.y + radius <= top
     }
 
-    pub fn is_circle_clear(&self, center: Vec2, radius: f32) -> bool {
+    /// Check that a circle doesn't touch any tile in `mask`.
+    pub fn is_circle_clear(&self, center: Vec2, radius: f32, mask: TileMask) -> bool {
         // Early bounds check
         if !self.is_within_bounds(center, radius) {
             return false;
@@ -122,7 +184,8 @@ impl CollisionMap {
 
         // Point collision if no radius
         if radius <= 0.0 {
-            return self.is_world_pos_walkable(center);
+            let grid_pos = self.world_to_grid(center);
+            return self.top_in_mask(grid_pos.x, grid_pos.y, mask).is_none();
         }
 
         // Find grid cells that could overlap the circle
@@ -137,14 +200,12 @@ impl CollisionMap {
                     return false;  // Out of bounds = blocked
                 }
 
-                if let Some(tile) = self.get_tile(gx, gy) {
-                    if !tile.is_walkable() {
-                        // Apply tile-specific collision adjustment
-                        let effective_radius = radius + tile.collision_adjustment() * self.tile_size;
-                        
-                        if self.circle_intersects_tile(center, effective_radius, gx, gy) {
-                            return false;
-                        }
+                if let Some(tile) = self.top_in_mask(gx, gy, mask) {
+                    // Apply tile-specific collision adjustment
+                    let effective_radius = radius + tile.collision_adjustment() * self.tile_size;
+                    
+                    if self.circle_intersects_tile(center, effective_radius, gx, gy) {
+                        return false;
                     }
                 }
             }
@@ -152,7 +213,8 @@ impl CollisionMap {
         true
     }
 
-    pub fn sweep_circle(&self, start: Vec2, end: Vec2, radius: f32) -> Vec2 {
+    /// Move a circle from `start` towards `end`, sliding along tiles in `mask`.
+    pub fn sweep_circle(&self, start: Vec2, end: Vec2, radius: f32, mask: TileMask) -> Vec2 {
         let delta = end - start;
         
         // No movement needed
@@ -169,19 +231,19 @@ impl CollisionMap {
         for _ in 0..steps {
             let candidate = pos + step_vec;
 
-            if self.is_circle_clear(candidate, radius) {
+            if self.is_circle_clear(candidate, radius, mask) {
                 pos = candidate;
             } else {
                 // Try sliding along X axis only
                 let try_x = Vec2::new(candidate.x, pos.y);
-                if self.is_circle_clear(try_x, radius) {
+                if self.is_circle_clear(try_x, radius, mask) {
                     pos = try_x;
                     continue;
                 }
 
                 // Try sliding along Y axis only
                 let try_y = Vec2::new(pos.x, candidate.y);
-                if self.is_circle_clear(try_y, radius) {
+                if self.is_circle_clear(try_y, radius, mask) {
                     pos = try_y;
                     continue;
                 }
@@ -193,17 +255,83 @@ impl CollisionMap {
         pos
     }
 
-    #[cfg(debug_assertions)]
     pub fn width(&self) -> i32 { self.width }
     
-    #[cfg(debug_assertions)]
     pub fn height(&self) -> i32 { self.height }
     
-    #[cfg(debug_assertions)]
     pub fn tile_size(&self) -> f32 { self.tile_size }
     
-    #[cfg(debug_assertions)]
     pub fn origin(&self) -> Vec2 { Vec2::new(self.origin_x, self.origin_y) }
 
 
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 3x3 map, 10 units per tile, whose cell (1, 1) holds `stack` bottom to top.
+    fn map_with_stack(stack: &[TileType]) -> CollisionMap {
+        let mut map = CollisionMap::new(3, 3, 10.0, 0.0, 0.0);
+        for &tile_type in stack {
+            map.push_tile(1, 1, tile_type);
+        }
+        map
+    }
+
+    #[test]
+    fn pushed_tiles_stack_bottom_to_top() {
+        let map = map_with_stack(&[TileType::Grass, TileType::Shore, TileType::Tree]);
+
+        assert_eq!(map.tiles_at(1, 1), &[TileType::Grass, TileType::Shore, TileType::Tree]);
+        assert_eq!(map.get_tile(1, 1), Some(TileType::Tree));
+        assert_eq!(map.top_blocking(1, 1), Some(TileType::Tree));
+        assert_eq!(map.ground_at(1, 1), Some(TileType::Shore));
+        assert!(!map.is_walkable(1, 1));
+    }
+
+    #[test]
+    fn empty_and_out_of_bounds_cells() {
+        let mut map = map_with_stack(&[]);
+        map.push_tile(3, 0, TileType::Rock);
+
+        assert_eq!(map.get_tile(0, 0), Some(TileType::Empty));
+        assert_eq!(map.ground_at(0, 0), None);
+        assert!(map.is_walkable(0, 0));
+        assert!(map.tiles_at(3, 0).is_empty());
+        assert_eq!(map.get_tile(3, 0), None);
+        assert!(!map.is_walkable(3, 0));
+    }
+
+    #[test]
+    fn replacing_a_middle_layer_keeps_the_layers_around_it() {
+        let mut map = map_with_stack(&[TileType::Grass, TileType::Water, TileType::Rock]);
+        map.replace_tile(1, 1, TileType::Water, TileType::Shore);
+
+        assert_eq!(map.tiles_at(1, 1), &[TileType::Grass, TileType::Shore, TileType::Rock]);
+        assert_eq!(map.ground_at(1, 1), Some(TileType::Shore));
+        assert_eq!(map.top_blocking(1, 1), Some(TileType::Rock));
+
+        map.replace_tile(1, 1, TileType::Rock, TileType::Dirt);
+        assert_eq!(map.top_blocking(1, 1), None);
+        assert!(map.is_walkable(1, 1));
+    }
+
+    #[test]
+    fn top_in_mask_skips_layers_outside_the_mask() {
+        let map = map_with_stack(&[TileType::Water, TileType::Grass, TileType::Stump]);
+
+        assert_eq!(map.top_in_mask(1, 1, TileMask::BLOCKING), Some(TileType::Stump));
+        assert_eq!(map.top_in_mask(1, 1, TileMask::BLOCKING_AIRBORNE), Some(TileType::Water));
+        assert_eq!(map.top_in_mask(1, 1, TileMask::SIGHT), None);
+        assert_eq!(map.top_in_mask(1, 1, TileMask::NONE.with(TileType::Grass)), Some(TileType::Grass));
+    }
+
+    #[test]
+    fn clearing_a_cell_empties_its_stack() {
+        let mut map = map_with_stack(&[TileType::Grass, TileType::Tree]);
+        map.clear_tiles(1, 1);
+
+        assert!(map.tiles_at(1, 1).is_empty());
+        assert!(map.is_walkable(1, 1));
+    }
+}
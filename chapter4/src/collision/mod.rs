@@ -1,17 +1,22 @@
 mod tile_type;
 mod map;
 mod systems;
+mod raycast;
+mod trigger;
 
 #[cfg(debug_assertions)]
 mod debug;
 
 use bevy::prelude::*;
+use crate::characters::physics;
 use crate::state::GameState;
 
 // Re-export commonly used types
-pub use tile_type::{TileType, TileMarker};
+pub use tile_type::{TileType, TileMarker, TileMask};
 pub use map::CollisionMap;
-pub use systems::CollisionMapBuilt;
+pub use systems::{CollisionMapBuilt, CollisionMapChanged};
+pub(crate) use systems::convert_water_edges_to_shore;
+pub use trigger::{TriggerZone, TriggerShape, TriggerEvent};
 
 #[cfg(debug_assertions)]
 pub use debug::DebugCollisionEnabled;
@@ -21,10 +26,25 @@ pub struct CollisionPlugin;
 impl Plugin for CollisionPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<CollisionMapBuilt>()
+            .add_message::<CollisionMapChanged>()
+            .add_message::<TriggerEvent>()
             .add_systems(
                 Update,
-                systems::build_collision_map
-                    .run_if(resource_equals(CollisionMapBuilt(false)))
+                (
+                    // Built while loading, the game starts once it exists
+                    systems::build_collision_map
+                        .run_if(resource_equals(CollisionMapBuilt(false))),
+                    systems::update_collision_map
+                        .run_if(resource_equals(CollisionMapBuilt(true)))
+                        .run_if(in_state(GameState::Playing)),
+                )
+                    .chain(),
+            )
+            // Zones follow the fixed-step positions, after movement has been resolved
+            .add_systems(
+                FixedUpdate,
+                trigger::update_trigger_zones
+                    .after(physics::apply_velocity)
                     .run_if(in_state(GameState::Playing)),
             );
 
@@ -38,6 +58,9 @@ impl Plugin for CollisionPlugin {
                         debug::toggle_debug_collision,
                         debug::debug_draw_collision,
                         debug::debug_player_position,
+                        debug::debug_facing_ray,
+                        debug::debug_draw_triggers,
+                        debug::debug_log_triggers,
                     )
                         .run_if(in_state(GameState::Playing)),
                 );
@@ -0,0 +1,276 @@
+use bevy::prelude::*;
+use super::{CollisionMap, TileMask, TileType};
+
+/// Result of a ray or circle cast against the collision map.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RaycastHit {
+    /// Grid cell that stopped the cast
+    pub tile: IVec2,
+    /// Type of the tile that stopped the cast
+    pub tile_type: TileType,
+    /// World position where the cast stopped
+    pub point: Vec2,
+    /// Surface normal at the hit (zero if the cast started inside a tile)
+    pub normal: Vec2,
+    /// Distance travelled along the cast direction
+    pub distance: f32,
+}
+
+/// Number of bisection steps used to refine a circle cast hit.
+const CIRCLE_CAST_REFINE_STEPS: u32 = 8;
+
+impl CollisionMap {
+    /// Cast a ray through the grid, cell by cell (DDA traversal).
+    /// Only tiles in `mask` stop the ray. Rays stop when they leave the map.
+    pub fn raycast(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        max_distance: f32,
+        mask: TileMask,
+    ) -> Option<RaycastHit> {
+        let dir = direction.normalize_or_zero();
+        if dir == Vec2::ZERO {
+            return None;
+        }
+
+        let tile_size = self.tile_size();
+        let mut cell = self.world_to_grid(origin);
+
+        // Starting inside a blocking tile is an immediate hit
+        if let Some(tile_type) = self.top_in_mask(cell.x, cell.y, mask) {
+            return Some(RaycastHit {
+                tile: cell,
+                tile_type,
+                point: origin,
+                normal: Vec2::ZERO,
+                distance: 0.0,
+            });
+        }
+
+        let step = IVec2::new(dir.x.signum() as i32, dir.y.signum() as i32);
+
+        // Distance along the ray needed to cross one whole cell on each axis
+        let t_delta = Vec2::new(
+            if dir.x != 0.0 { tile_size / dir.x.abs() } else { f32::INFINITY },
+            if dir.y != 0.0 { tile_size / dir.y.abs() } else { f32::INFINITY },
+        );
+
+        // Distance along the ray to the first cell boundary on each axis
+        let cell_min = self.origin() + cell.as_vec2() * tile_size;
+        let next_boundary = Vec2::new(
+            if step.x > 0 { cell_min.x + tile_size } else { cell_min.x },
+            if step.y > 0 { cell_min.y + tile_size } else { cell_min.y },
+        );
+        let mut t_max = Vec2::new(
+            if dir.x != 0.0 { (next_boundary.x - origin.x) / dir.x } else { f32::INFINITY },
+            if dir.y != 0.0 { (next_boundary.y - origin.y) / dir.y } else { f32::INFINITY },
+        );
+
+        loop {
+            // Step into whichever neighbouring cell the ray reaches first
+            let (distance, normal) = if t_max.x < t_max.y {
+                cell.x += step.x;
+                let t = t_max.x;
+                t_max.x += t_delta.x;
+                (t, Vec2::new(-step.x as f32, 0.0))
+            } else {
+                cell.y += step.y;
+                let t = t_max.y;
+                t_max.y += t_delta.y;
+                (t, Vec2::new(0.0, -step.y as f32))
+            };
+
+            if distance > max_distance || !self.in_bounds(cell.x, cell.y) {
+                return None;
+            }
+
+            if let Some(tile_type) = self.top_in_mask(cell.x, cell.y, mask) {
+                return Some(RaycastHit {
+                    tile: cell,
+                    tile_type,
+                    point: origin + dir * distance,
+                    normal,
+                    distance,
+                });
+            }
+        }
+    }
+
+    /// Sweep a circle along a direction and report the first tile in `mask` it touches.
+    /// `point` is the circle center at the moment of contact.
+    pub fn circle_cast(
+        &self,
+        origin: Vec2,
+        direction: Vec2,
+        radius: f32,
+        max_distance: f32,
+        mask: TileMask,
+    ) -> Option<RaycastHit> {
+        let dir = direction.normalize_or_zero();
+        if dir == Vec2::ZERO {
+            return None;
+        }
+
+        if let Some((tile, tile_type)) = self.circle_overlap(origin, radius, mask) {
+            return Some(RaycastHit {
+                tile,
+                tile_type,
+                point: origin,
+                normal: Vec2::ZERO,
+                distance: 0.0,
+            });
+        }
+
+        // March in quarter-tile steps (same as sweep_circle) until something overlaps
+        let max_step = self.tile_size() * 0.25;
+        let steps = (max_distance / max_step).ceil().max(1.0) as u32;
+        let step_length = max_distance / steps as f32;
+
+        let mut clear = 0.0;
+        for i in 1..=steps {
+            let distance = step_length * i as f32;
+            if self.circle_overlap(origin + dir * distance, radius, mask).is_none() {
+                clear = distance;
+                continue;
+            }
+
+            // Narrow down the contact distance between the last clear and first blocked step
+            let mut blocked = distance;
+            for _ in 0..CIRCLE_CAST_REFINE_STEPS {
+                let mid = (clear + blocked) * 0.5;
+                if self.circle_overlap(origin + dir * mid, radius, mask).is_some() {
+                    blocked = mid;
+                } else {
+                    clear = mid;
+                }
+            }
+
+            let center = origin + dir * blocked;
+            let (tile, tile_type) = self.circle_overlap(center, radius, mask)?;
+            let closest = self.closest_point_on_tile(center, tile);
+            let normal = (center - closest).normalize_or(-dir);
+
+            return Some(RaycastHit {
+                tile,
+                tile_type,
+                point: origin + dir * clear,
+                normal,
+                distance: clear,
+            });
+        }
+
+        None
+    }
+
+    /// True when nothing in `mask` lies on the straight line between `from` and `to`.
+    pub fn has_line_of_sight(&self, from: Vec2, to: Vec2, mask: TileMask) -> bool {
+        let delta = to - from;
+        self.raycast(from, delta, delta.length(), mask).is_none()
+    }
+
+    /// First tile in `mask` overlapped by a circle, if any.
+    fn circle_overlap(&self, center: Vec2, radius: f32, mask: TileMask) -> Option<(IVec2, TileType)> {
+        let min = self.world_to_grid(center - Vec2::splat(radius));
+        let max = self.world_to_grid(center + Vec2::splat(radius));
+
+        for gy in min.y..=max.y {
+            for gx in min.x..=max.x {
+                let tile = IVec2::new(gx, gy);
+                let Some(tile_type) = self.top_in_mask(gx, gy, mask) else { continue };
+
+                if center.distance_squared(self.closest_point_on_tile(center, tile)) <= radius * radius {
+                    return Some((tile, tile_type));
+                }
+            }
+        }
+        None
+    }
+
+    fn closest_point_on_tile(&self, point: Vec2, tile: IVec2) -> Vec2 {
+        let tile_min = self.origin() + tile.as_vec2() * self.tile_size();
+        let tile_max = tile_min + Vec2::splat(self.tile_size());
+        point.clamp(tile_min, tile_max)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TILE: f32 = 10.0;
+
+    /// A 10x10 map of grass, 10 units per tile, origin at (0, 0), with `tiles` on top.
+    fn map_with(tiles: &[(i32, i32, TileType)]) -> CollisionMap {
+        let mut map = CollisionMap::new(10, 10, TILE, 0.0, 0.0);
+        for y in 0..10 {
+            for x in 0..10 {
+                map.push_tile(x, y, TileType::Grass);
+            }
+        }
+        for &(x, y, tile_type) in tiles {
+            map.push_tile(x, y, tile_type);
+        }
+        map
+    }
+
+    #[test]
+    fn raycast_reports_tile_point_normal_and_distance() {
+        let map = map_with(&[(5, 2, TileType::Tree)]);
+        let hit = map.raycast(Vec2::new(15.0, 25.0), Vec2::X, 100.0, TileMask::BLOCKING).unwrap();
+
+        assert_eq!(hit.tile, IVec2::new(5, 2));
+        assert_eq!(hit.tile_type, TileType::Tree);
+        assert!(hit.point.distance(Vec2::new(50.0, 25.0)) < 1e-3);
+        assert_eq!(hit.normal, Vec2::NEG_X);
+        assert!((hit.distance - 35.0).abs() < 1e-3);
+    }
+
+    #[test]
+    fn raycast_misses_beyond_max_distance() {
+        let map = map_with(&[(5, 2, TileType::Tree)]);
+        assert!(map.raycast(Vec2::new(15.0, 25.0), Vec2::X, 30.0, TileMask::BLOCKING).is_none());
+    }
+
+    #[test]
+    fn raycast_starting_inside_a_blocking_tile_hits_at_once() {
+        let map = map_with(&[(5, 2, TileType::Rock)]);
+        let origin = Vec2::new(55.0, 25.0);
+        let hit = map.raycast(origin, Vec2::Y, 100.0, TileMask::BLOCKING).unwrap();
+
+        assert_eq!(hit.tile, IVec2::new(5, 2));
+        assert_eq!(hit.tile_type, TileType::Rock);
+        assert_eq!(hit.point, origin);
+        assert_eq!(hit.normal, Vec2::ZERO);
+        assert_eq!(hit.distance, 0.0);
+    }
+
+    #[test]
+    fn circle_cast_stops_where_the_circle_touches() {
+        let map = map_with(&[(5, 2, TileType::Tree)]);
+        let hit = map
+            .circle_cast(Vec2::new(15.0, 25.0), Vec2::X, 4.0, 100.0, TileMask::BLOCKING)
+            .unwrap();
+
+        // The circle's edge reaches the tree's left side (x = 50) when its center is at 46
+        assert_eq!(hit.tile, IVec2::new(5, 2));
+        assert!((hit.distance - 31.0).abs() < 0.05, "distance {}", hit.distance);
+        assert!((hit.point.x - 46.0).abs() < 0.05);
+        assert!(hit.normal.distance(Vec2::NEG_X) < 1e-3);
+    }
+
+    #[test]
+    fn line_of_sight_depends_on_the_mask() {
+        let map = map_with(&[(5, 5, TileType::Water), (5, 8, TileType::Tree)]);
+
+        // Water blocks walking, not sight
+        let (from, to) = (Vec2::new(15.0, 55.0), Vec2::new(85.0, 55.0));
+        assert!(map.has_line_of_sight(from, to, TileMask::SIGHT));
+        assert!(!map.has_line_of_sight(from, to, TileMask::BLOCKING));
+
+        // Trees block both
+        let (from, to) = (Vec2::new(15.0, 85.0), Vec2::new(85.0, 85.0));
+        assert!(!map.has_line_of_sight(from, to, TileMask::SIGHT));
+        assert!(!map.has_line_of_sight(from, to, TileMask::BLOCKING));
+    }
+}
@@ -1,5 +1,5 @@
 use bevy::prelude::*;
-use std::collections::{HashMap, hash_map::Entry};
+use std::collections::HashMap;
 
 use super::{CollisionMap, TileMarker, TileType};
 use crate::config::map::{TILE_SIZE, GRID_X, GRID_Y};
@@ -8,14 +8,61 @@ use crate::config::map::{TILE_SIZE, GRID_X, GRID_Y};
 #[derive(Resource, Default, PartialEq, Eq)]
 pub struct CollisionMapBuilt(pub bool);
 
+/// Sent whenever tiles change at runtime and the collision map is patched.
+/// The rectangle is inclusive and in collision map grid coordinates.
+#[derive(Message, Debug, Clone, Copy)]
+pub struct CollisionMapChanged {
+    pub min: IVec2,
+    pub max: IVec2,
+}
+
+/// Remembers which cell every tile entity occupies.
+/// Lets runtime changes patch only the cells they touch, even after
+/// the tile entity has been despawned.
+#[derive(Resource, Default)]
+pub struct TileCellIndex {
+    /// Raw grid position of the map's bottom-left cell
+    offset: IVec2,
+    entities: HashMap<Entity, (IVec2, TileType)>,
+    cells: HashMap<IVec2, Vec<Entity>>,
+}
+
+impl TileCellIndex {
+    fn insert(&mut self, entity: Entity, cell: IVec2, tile_type: TileType) {
+        self.remove(entity);
+        self.entities.insert(entity, (cell, tile_type));
+        self.cells.entry(cell).or_default().push(entity);
+    }
+
+    fn remove(&mut self, entity: Entity) -> Option<IVec2> {
+        let (cell, _) = self.entities.remove(&entity)?;
+        if let Some(entities) = self.cells.get_mut(&cell) {
+            entities.retain(|e| *e != entity);
+        }
+        Some(cell)
+    }
+}
+
+/// Raw grid position of a tile, before the map offset is applied.
+fn tile_grid_position(transform: &Transform) -> IVec2 {
+    // Calculate grid origin (centered map)
+    let grid_origin_x = -TILE_SIZE * GRID_X as f32 / 2.0;
+    let grid_origin_y = -TILE_SIZE * GRID_Y as f32 / 2.0;
+
+    IVec2::new(
+        ((transform.translation.x - grid_origin_x) / TILE_SIZE).floor() as i32,
+        ((transform.translation.y - grid_origin_y) / TILE_SIZE).floor() as i32,
+    )
+}
+
 pub fn build_collision_map(
     mut commands: Commands,
     mut built: ResMut<CollisionMapBuilt>,
-    tile_query: Query<(&TileMarker, &Transform)>,
+    tile_query: Query<(Entity, &TileMarker, &Transform)>,
 ) {
     // Need at least one tile to proceed
     let mut tile_iter = tile_query.iter();
-    let Some((first_marker, first_transform)) = tile_iter.next() else {
+    let Some(first_tile) = tile_iter.next() else {
         return; // WFC hasn't generated tiles yet
     };
 
@@ -26,42 +73,31 @@ pub fn build_collision_map(
     // Track bounds and layer info
     let (mut min_x, mut max_x) = (i32::MAX, i32::MIN);
     let (mut min_y, mut max_y) = (i32::MAX, i32::MIN);
-    let mut layer_tracker: HashMap<(i32, i32), (TileType, f32)> = HashMap::new();
-    let mut tile_count: usize = 0;
-
-    // Process all tiles, keeping only the topmost at each position
-    let mut process_tile = |marker: &TileMarker, transform: &Transform| {
-        tile_count += 1;
+    let mut layer_tracker: HashMap<(i32, i32), Vec<(TileType, f32)>> = HashMap::new();
+    let mut tile_cells: Vec<(Entity, IVec2, TileType)> = Vec::new();
 
-        let world_x = transform.translation.x;
-        let world_y = transform.translation.y;
+    // Process all tiles, collecting every layer at each position
+    let mut process_tile = |(entity, marker, transform): (Entity, &TileMarker, &Transform)| {
+        let grid = tile_grid_position(transform);
         let world_z = transform.translation.z;
-        
-        let grid_x = ((world_x - grid_origin_x) / TILE_SIZE).floor() as i32;
-        let grid_y = ((world_y - grid_origin_y) / TILE_SIZE).floor() as i32;
-
-        min_x = min_x.min(grid_x);
-        max_x = max_x.max(grid_x);
-        min_y = min_y.min(grid_y);
-        max_y = max_y.max(grid_y);
-
-        // Keep only the topmost layer (highest Z)
-        match layer_tracker.entry((grid_x, grid_y)) {
-            Entry::Occupied(mut entry) => {
-                if world_z > entry.get().1 {
-                    *entry.get_mut() = (marker.tile_type, world_z);
-                }
-            }
-            Entry::Vacant(entry) => {
-                entry.insert((marker.tile_type, world_z));
-            }
-        }
+
+        min_x = min_x.min(grid.x);
+        max_x = max_x.max(grid.x);
+        min_y = min_y.min(grid.y);
+        max_y = max_y.max(grid.y);
+
+        // Keep every layer, the stack is sorted by Z once all tiles are in
+        layer_tracker
+            .entry((grid.x, grid.y))
+            .or_default()
+            .push((marker.tile_type, world_z));
+        tile_cells.push((entity, grid, marker.tile_type));
     };
 
     // Process first tile and remaining
-    process_tile(first_marker, first_transform);
-    for (marker, transform) in tile_iter {
-        process_tile(marker, transform);
+    process_tile(first_tile);
+    for tile in tile_iter {
+        process_tile(tile);
     }
 
     // Calculate actual dimensions
@@ -77,28 +113,120 @@ pub fn build_collision_map(
         grid_origin_y,
     );
 
-    // Populate the map from layer tracker
-    for ((grid_x, grid_y), (tile_type, _z)) in layer_tracker.iter() {
+    // Populate the map from layer tracker, bottom layer first
+    for ((grid_x, grid_y), layers) in layer_tracker.iter_mut() {
         // Convert world grid to local array coordinates
-        let local_x = grid_x - min_x;
-        let local_y = grid_y - min_y;
-        map.set_tile(local_x, local_y, *tile_type);
+        let local_x = *grid_x - min_x;
+        let local_y = *grid_y - min_y;
+
+        layers.sort_by(|a, b| a.1.total_cmp(&b.1));
+        for (tile_type, _z) in layers.iter() {
+            map.push_tile(local_x, local_y, *tile_type);
+        }
+    }
+
+    // Remember where every tile lives for incremental updates
+    let offset = IVec2::new(min_x, min_y);
+    let mut index = TileCellIndex { offset, ..default() };
+    for (entity, grid, tile_type) in tile_cells {
+        index.insert(entity, grid - offset, tile_type);
     }
 
     // Post-processing: Convert water edges to shore
-    convert_water_edges_to_shore(&mut map);
+    let (map_min, map_max) = (IVec2::ZERO, IVec2::new(map.width() - 1, map.height() - 1));
+    convert_water_edges_to_shore(&mut map, map_min, map_max);
     // Insert as resource and mark built
     commands.insert_resource(map);
+    commands.insert_resource(index);
     built.0 = true;
 }
 
-fn convert_water_edges_to_shore(map: &mut CollisionMap) {
+/// Tiles whose type or position changed since the last update (includes newly added tiles).
+type ChangedTileFilter = (With<TileMarker>, Or<(Changed<TileMarker>, Changed<Transform>)>);
+
+/// Patches the collision map when tiles are added, changed or removed at runtime.
+pub fn update_collision_map(
+    map: Option<ResMut<CollisionMap>>,
+    index: Option<ResMut<TileCellIndex>>,
+    tile_query: Query<(&TileMarker, &Transform)>,
+    changed_query: Query<Entity, ChangedTileFilter>,
+    mut removed: RemovedComponents<TileMarker>,
+    mut changed_writer: MessageWriter<CollisionMapChanged>,
+) {
+    let (Some(mut map), Some(mut index)) = (map, index) else { return };
+
+    let mut dirty: Vec<IVec2> = Vec::new();
+
+    // Removed tiles: the index still knows where they were
+    for entity in removed.read() {
+        if let Some(cell) = index.remove(entity) {
+            dirty.push(cell);
+        }
+    }
+
+    // Added or changed tiles: dirty both the old and new cell
+    for entity in changed_query.iter() {
+        let Ok((marker, transform)) = tile_query.get(entity) else { continue };
+        let cell = tile_grid_position(transform) - index.offset;
+
+        match index.entities.get(&entity) {
+            Some(&(old_cell, old_type)) if old_cell == cell && old_type == marker.tile_type => continue,
+            Some(&(old_cell, _)) => dirty.push(old_cell),
+            None => {}
+        }
+
+        index.insert(entity, cell, marker.tile_type);
+        dirty.push(cell);
+    }
+
+    if dirty.is_empty() {
+        return;
+    }
+
+    // Grow the dirty area by one cell so neighbouring shores are re-evaluated
+    let map_max = IVec2::new(map.width() - 1, map.height() - 1);
+    let min = dirty.iter().fold(IVec2::MAX, |acc, cell| acc.min(*cell)) - IVec2::ONE;
+    let max = dirty.iter().fold(IVec2::MIN, |acc, cell| acc.max(*cell)) + IVec2::ONE;
+    let (min, max) = (min.clamp(IVec2::ZERO, map_max), max.clamp(IVec2::ZERO, map_max));
+
+    // Rebuild each affected cell's layer stack from its tiles
+    for y in min.y..=max.y {
+        for x in min.x..=max.x {
+            let mut layers: Vec<(TileType, f32)> = index
+                .cells
+                .get(&IVec2::new(x, y))
+                .into_iter()
+                .flatten()
+                .filter_map(|entity| tile_query.get(*entity).ok())
+                .map(|(marker, transform)| (marker.tile_type, transform.translation.z))
+                .collect();
+            layers.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+            map.clear_tiles(x, y);
+            for (tile_type, _z) in layers {
+                map.push_tile(x, y, tile_type);
+            }
+        }
+    }
+
+    convert_water_edges_to_shore(&mut map, min, max);
+    changed_writer.write(CollisionMapChanged { min, max });
+}
+
+/// Convert water tiles touching walkable ground into shore, within an inclusive rectangle.
+pub(crate) fn convert_water_edges_to_shore(map: &mut CollisionMap, min: IVec2, max: IVec2) {
     let mut shores = Vec::new();
 
+    // Shore is only ever produced by this pass, so treat it as the water it
+    // replaced when looking at neighbours outside the rectangle
+    let is_ground = |map: &CollisionMap, x: i32, y: i32| {
+        map.is_walkable(x, y) && !map.tiles_at(x, y).contains(&TileType::Shore)
+    };
+
     // Find water tiles that touch walkable tiles
-    for y in 0..map.height() {
-        for x in 0..map.width() {
-            if map.get_tile(x, y) != Some(TileType::Water) {
+    for y in min.y..=max.y {
+        for x in min.x..=max.x {
+            if map.top_blocking(x, y) != Some(TileType::Water) {
                 continue;
             }
 
@@ -111,7 +239,7 @@ fn convert_water_edges_to_shore(map: &mut CollisionMap) {
             ];
 
             for (nx, ny) in neighbors {
-                if map.is_walkable(nx, ny) {
+                if is_ground(map, nx, ny) {
                     shores.push((x, y));
                     break;
                 }
@@ -120,8 +248,93 @@ fn convert_water_edges_to_shore(map: &mut CollisionMap) {
     }
 
     for (x, y) in shores {
-        map.set_tile(x, y, TileType::Shore);
+        map.replace_tile(x, y, TileType::Water, TileType::Shore);
+    }
+
+
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::message::Messages;
+    use bevy::ecs::system::RunSystemOnce;
+
+    /// Raw grid position of the test map's bottom-left cell.
+    const OFFSET: IVec2 = IVec2::new(4, 6);
+
+    fn tile(cell: IVec2, tile_type: TileType, z: f32) -> (TileMarker, Transform) {
+        let origin = -TILE_SIZE * Vec2::new(GRID_X as f32, GRID_Y as f32) / 2.0;
+        let center = origin + ((cell + OFFSET).as_vec2() + 0.5) * TILE_SIZE;
+        (TileMarker::new(tile_type), Transform::from_translation(center.extend(z)))
+    }
+
+    /// A 7x7 grass map with a 5x5 pond whose center cell is left dry.
+    fn pond_app() -> App {
+        let mut app = App::new();
+        app.init_resource::<CollisionMapBuilt>()
+            .add_message::<CollisionMapChanged>()
+            .add_systems(Update, update_collision_map);
+
+        for y in 0..7 {
+            for x in 0..7 {
+                let cell = IVec2::new(x, y);
+                app.world_mut().spawn(tile(cell, TileType::Grass, 0.0));
+                let in_pond = (1..=5).contains(&x) && (1..=5).contains(&y);
+                if in_pond && cell != IVec2::new(3, 3) {
+                    app.world_mut().spawn(tile(cell, TileType::Water, 1.0));
+                }
+            }
+        }
+        app.world_mut().run_system_once(build_collision_map).unwrap();
+        app
+    }
+
+    fn changes(app: &mut App) -> Vec<(IVec2, IVec2)> {
+        let mut messages = app.world_mut().resource_mut::<Messages<CollisionMapChanged>>();
+        messages.drain().map(|changed| (changed.min, changed.max)).collect()
     }
 
+    #[test]
+    fn spawning_and_removing_water_patches_the_cells_around_it() {
+        let mut app = pond_app();
+        app.update();
+        assert!(changes(&mut app).is_empty(), "nothing changed yet");
 
-}
\ No newline at end of file
+        let map = app.world().resource::<CollisionMap>();
+        assert_eq!(app.world().resource::<TileCellIndex>().offset, OFFSET);
+        assert_eq!(map.top_blocking(2, 2), None, "the pond shores face the dry center");
+        assert_eq!(map.tiles_at(2, 2), &[TileType::Grass, TileType::Shore]);
+
+        // Fill the dry center: the inner ring no longer touches ground
+        let center = IVec2::new(3, 3);
+        let water = app.world_mut().spawn(tile(center, TileType::Water, 1.0)).id();
+        app.update();
+
+        assert_eq!(changes(&mut app), vec![(IVec2::new(2, 2), IVec2::new(4, 4))]);
+        let map = app.world().resource::<CollisionMap>();
+        for y in 2..=4 {
+            for x in 2..=4 {
+                assert_eq!(map.top_blocking(x, y), Some(TileType::Water), "cell ({x}, {y})");
+            }
+        }
+        assert_eq!(map.tiles_at(1, 1), &[TileType::Grass, TileType::Shore], "outer shore kept");
+        let index = app.world().resource::<TileCellIndex>();
+        assert_eq!(index.entities.get(&water), Some(&(center, TileType::Water)));
+        assert!(index.cells[&center].contains(&water));
+
+        // Drain it again: the inner ring turns back into shore
+        app.world_mut().despawn(water);
+        app.update();
+
+        assert_eq!(changes(&mut app), vec![(IVec2::new(2, 2), IVec2::new(4, 4))]);
+        let map = app.world().resource::<CollisionMap>();
+        assert_eq!(map.tiles_at(3, 3), &[TileType::Grass]);
+        for (x, y) in [(2, 2), (3, 2), (4, 4), (2, 3)] {
+            assert_eq!(map.tiles_at(x, y), &[TileType::Grass, TileType::Shore], "cell ({x}, {y})");
+        }
+        let index = app.world().resource::<TileCellIndex>();
+        assert!(!index.entities.contains_key(&water));
+        assert!(!index.cells[&center].contains(&water));
+    }
+}
@@ -10,29 +10,95 @@ pub enum TileType {
     Dirt,
     Grass,
     YellowGrass,
+    Sand,   // Desert ground
+    Snow,   // Snowfield ground
+    Mud,    // Swamp ground
     Shore,  // Water edges (walkable)
     // Non-walkable obstacles
     Water,
     Tree,
     Rock,
+    Stump,
 }
 
 impl TileType {
+    /// Every tile type, in declaration order.
+    pub const ALL: [TileType; 12] = [
+        TileType::Empty,
+        TileType::Dirt,
+        TileType::Grass,
+        TileType::YellowGrass,
+        TileType::Sand,
+        TileType::Snow,
+        TileType::Mud,
+        TileType::Shore,
+        TileType::Water,
+        TileType::Tree,
+        TileType::Rock,
+        TileType::Stump,
+    ];
+
+    /// Look up a tile type by its variant name (as written in map files).
+    pub fn from_name(name: &str) -> Option<TileType> {
+        TileType::ALL.into_iter().find(|tile_type| format!("{tile_type:?}") == name)
+    }
+
     /// Check if this tile type allows movement through it.
-    pub fn is_walkable(&self) -> bool {
-        !matches!(self, TileType::Water | TileType::Tree | TileType::Rock)
+    pub const fn is_walkable(&self) -> bool {
+        !matches!(self, TileType::Water | TileType::Tree | TileType::Rock | TileType::Stump)
+    }
+
+    /// Check if this obstacle is low enough to jump over.
+    pub const fn is_low(&self) -> bool {
+        matches!(self, TileType::Rock | TileType::Stump)
     }
 
     /// Get the collision adjustment for this tile type.
     /// Positive = push player away, negative = allow corner cutting.
     pub fn collision_adjustment(&self) -> f32 {
         match self {
-            TileType::Tree | TileType::Rock => -0.2,  // Allow cutting corners
+            TileType::Tree | TileType::Rock | TileType::Stump => -0.2,  // Allow cutting corners
             _ => 0.0,
         }
     }
 }
 
+/// A set of tile types, used to choose which tiles stop a query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct TileMask(u32);
+
+impl TileMask {
+    pub const NONE: Self = Self(0);
+    /// Everything that blocks movement.
+    pub const BLOCKING: Self = Self::blocking(false);
+    /// Everything that blocks movement once a jump clears low obstacles.
+    pub const BLOCKING_AIRBORNE: Self = Self::blocking(true);
+    /// Everything that blocks sight (you can see across water).
+    pub const SIGHT: Self = Self::NONE.with(TileType::Tree).with(TileType::Rock);
+
+    const fn blocking(airborne: bool) -> Self {
+        let mut mask = Self::NONE;
+        let mut i = 0;
+        while i < TileType::ALL.len() {
+            let tile_type = TileType::ALL[i];
+            let cleared = airborne && tile_type.is_low();
+            if !tile_type.is_walkable() && !cleared {
+                mask = mask.with(tile_type);
+            }
+            i += 1;
+        }
+        mask
+    }
+
+    pub const fn with(self, tile_type: TileType) -> Self {
+        Self(self.0 | 1 << tile_type as u32)
+    }
+
+    pub const fn contains(self, tile_type: TileType) -> bool {
+        self.0 & (1 << tile_type as u32) != 0
+    }
+}
+
 #[derive(Component, Debug, Clone)]
 pub struct TileMarker {
     pub tile_type: TileType,
@@ -0,0 +1,153 @@
+use bevy::prelude::*;
+use std::collections::HashSet;
+
+use super::{CollisionMap, TileMask};
+use crate::characters::collider::Collider;
+use crate::characters::physics::PhysicsPosition;
+
+/// Area that reports when colliders enter, stay in, or leave it.
+/// Useful for map transitions, damage zones, cutscene starts and region names.
+#[derive(Component, Debug, Clone)]
+#[require(TriggerOccupants)]
+pub struct TriggerZone {
+    /// Display name (e.g. a region name for the HUD)
+    pub name: String,
+    pub shape: TriggerShape,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriggerShape {
+    /// Rectangle centered on the zone's transform
+    Rect { half_size: Vec2 },
+    /// Circle centered on the zone's transform
+    Circle { radius: f32 },
+    /// Every cell whose layer stack contains one of these tile types
+    Tiles(TileMask),
+    /// Inclusive rectangle of collision map cells
+    Region { min: IVec2, max: IVec2 },
+}
+
+impl TriggerZone {
+    pub fn new(name: impl Into<String>, shape: TriggerShape) -> Self {
+        Self {
+            name: name.into(),
+            shape,
+        }
+    }
+
+    /// Check whether a collider circle overlaps this zone.
+    /// Tile-based shapes test the cell under the collider's center.
+    fn contains(&self, zone_pos: Vec2, map: Option<&CollisionMap>, center: Vec2, radius: f32) -> bool {
+        match self.shape {
+            TriggerShape::Rect { half_size } => {
+                let closest = center.clamp(zone_pos - half_size, zone_pos + half_size);
+                center.distance_squared(closest) <= radius * radius
+            }
+            TriggerShape::Circle { radius: zone_radius } => {
+                center.distance(zone_pos) <= zone_radius + radius
+            }
+            TriggerShape::Tiles(mask) => map.is_some_and(|map| {
+                let grid = map.world_to_grid(center);
+                map.tiles_at(grid.x, grid.y).iter().any(|tile| mask.contains(*tile))
+            }),
+            TriggerShape::Region { min, max } => map.is_some_and(|map| {
+                let grid = map.world_to_grid(center);
+                grid.cmpge(min).all() && grid.cmple(max).all()
+            }),
+        }
+    }
+}
+
+/// Entities currently inside a trigger zone.
+#[derive(Component, Debug, Default)]
+pub struct TriggerOccupants(pub HashSet<Entity>);
+
+/// Sent when a collider enters, stays in, or leaves a trigger zone.
+/// Written once per fixed physics tick, so `Stay` arrives at the tick rate
+/// (`FIXED_TIMESTEP_HZ`), not once per rendered frame.
+#[derive(Message, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriggerEvent {
+    Enter { zone: Entity, entity: Entity },
+    Stay { zone: Entity, entity: Entity },
+    Exit { zone: Entity, entity: Entity },
+}
+
+/// Runs in `FixedUpdate` after movement, so occupancy follows the simulated
+/// ground position that collision uses rather than the interpolated (and
+/// jump-lifted) `Transform`.
+pub fn update_trigger_zones(
+    map: Option<Res<CollisionMap>>,
+    mut zone_query: Query<(Entity, &TriggerZone, Option<&Transform>, &mut TriggerOccupants)>,
+    collider_query: Query<(Entity, &PhysicsPosition, &Collider)>,
+    mut trigger_writer: MessageWriter<TriggerEvent>,
+) {
+    let map = map.as_deref();
+
+    for (zone, trigger, zone_transform, mut occupants) in zone_query.iter_mut() {
+        let zone_pos = zone_transform.map_or(Vec2::ZERO, |t| t.translation.truncate());
+
+        let inside: HashSet<Entity> = collider_query
+            .iter()
+            .filter(|(_, position, collider)| {
+                trigger.contains(zone_pos, map, collider.position_at(position.current), collider.radius)
+            })
+            .map(|(entity, _, _)| entity)
+            .collect();
+
+        for &entity in inside.iter() {
+            if occupants.0.contains(&entity) {
+                trigger_writer.write(TriggerEvent::Stay { zone, entity });
+            } else {
+                trigger_writer.write(TriggerEvent::Enter { zone, entity });
+            }
+        }
+
+        // Despawned colliders also count as leaving
+        for &entity in occupants.0.difference(&inside) {
+            trigger_writer.write(TriggerEvent::Exit { zone, entity });
+        }
+
+        // Only touch the component when membership changed
+        if occupants.0 != inside {
+            occupants.0 = inside;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::message::Messages;
+    use bevy::ecs::system::RunSystemOnce;
+
+    /// Move the collider to `position`, run one tick and return what it sent.
+    fn tick(world: &mut World, collider: Entity, position: Vec2) -> Vec<TriggerEvent> {
+        let mut physics = world.get_mut::<PhysicsPosition>(collider).unwrap();
+        physics.previous = physics.current;
+        physics.current = position;
+        world.run_system_once(update_trigger_zones).unwrap();
+        world.resource_mut::<Messages<TriggerEvent>>().drain().collect()
+    }
+
+    #[test]
+    fn zones_follow_the_physics_position_once_per_tick() {
+        let mut world = World::new();
+        world.init_resource::<Messages<TriggerEvent>>();
+        let zone = world
+            .spawn(TriggerZone::new("Camp", TriggerShape::Rect { half_size: Vec2::splat(10.0) }))
+            .id();
+        // Mid-jump below the zone: the rendered transform is lifted over it, the ground is not
+        let entity = world
+            .spawn((
+                PhysicsPosition::new(Vec2::new(0.0, -30.0)),
+                Transform::from_xyz(0.0, 0.0, 0.0),
+                Collider { radius: 2.0, offset: Vec2::ZERO },
+            ))
+            .id();
+
+        assert_eq!(tick(&mut world, entity, Vec2::new(0.0, -30.0)), vec![]);
+        assert_eq!(tick(&mut world, entity, Vec2::new(11.0, 0.0)), vec![TriggerEvent::Enter { zone, entity }]);
+        assert_eq!(tick(&mut world, entity, Vec2::ZERO), vec![TriggerEvent::Stay { zone, entity }]);
+        assert_eq!(tick(&mut world, entity, Vec2::new(0.0, 13.0)), vec![TriggerEvent::Exit { zone, entity }]);
+    }
+}
@@ -8,6 +8,9 @@ pub mod player {
     
     /// Visual scale of the player sprite
     pub const PLAYER_SCALE: f32 = 0.8;
+
+    /// Jump height above which low obstacles (rocks, stumps) no longer block
+    pub const LOW_OBSTACLE_CLEARANCE: f32 = 12.0;
 }
 
 /// Map/terrain configuration
@@ -18,4 +21,49 @@ pub mod map {
     /// Grid dimensions
     pub const GRID_X: u32 = 25;
     pub const GRID_Y: u32 = 18;
-}
\ No newline at end of file
+
+    /// Load this Tiled map (relative to the assets folder) instead of running WFC,
+    /// e.g. `Some("maps/level_1.tmx")`
+    pub const TILED_MAP: Option<&str> = None;
+
+    /// Where F5 saves the current map (.tmx and .json), inside the assets folder
+    /// so it can be set as TILED_MAP ("maps/generated.tmx")
+    pub const EXPORT_DIR: &str = "src/assets/maps";
+    pub const EXPORT_NAME: &str = "generated";
+
+    /// Seeds tried before giving up on generating a map
+    pub const GENERATION_ATTEMPTS: u32 = 3;
+
+    /// Times the WFC restarts on its own within one attempt
+    pub const GENERATOR_RETRIES: u32 = 50;
+}
+
+/// Biome layout, from two noise fields (temperature and moisture) in 0..1
+pub mod biome {
+    /// Rough size of a biome region, in tiles
+    pub const NOISE_SCALE: f32 = 10.0;
+
+    /// Colder than this is snowfield
+    pub const COLD: f32 = 0.3;
+
+    /// Hotter than this is desert
+    pub const HOT: f32 = 0.7;
+
+    /// Wetter than this (and neither cold nor hot) is swamp
+    pub const WET: f32 = 0.65;
+}
+
+/// Physics configuration
+pub mod physics {
+    /// Movement and collision ticks per second (runs in `FixedUpdate`)
+    pub const FIXED_TIMESTEP_HZ: f64 = 60.0;
+}
+
+/// Fading of props (tree tops) that hide the player
+pub mod occlusion {
+    /// Opacity of a prop while the player is behind it
+    pub const FADED_ALPHA: f32 = 0.4;
+
+    /// Opacity change per second (1.0 / FADE_SPEED seconds for a full fade)
+    pub const FADE_SPEED: f32 = 4.0;
+}
@@ -0,0 +1,6 @@
+// Game modules, shared by the game binary (main.rs) and the tools in src/bin
+pub mod map;
+pub mod characters;
+pub mod state;
+pub mod collision;
+pub mod config;
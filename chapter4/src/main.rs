@@ -1,17 +1,10 @@
-mod map;
-mod characters;
-mod state; 
-mod collision;
-mod config;
-
 use bevy::{
     prelude::*,
     window::{Window, WindowPlugin, WindowResolution},
 };
 
-use bevy_procedural_tilemaps::prelude::*;
-
-use crate::map::generate::{map_pixel_dimensions, setup_generator};
+use chapter4::{characters, collision, map, state};
+use chapter4::map::generate::map_pixel_dimensions;
 
 fn main() {
     let map_size = map_pixel_dimensions();
@@ -34,11 +27,11 @@ fn main() {
                 })
                 .set(ImagePlugin::default_nearest()),
         )
-        .add_plugins(ProcGenSimplePlugin::<Cartesian3D, Sprite>::default())
         .add_plugins(state::StatePlugin)
+        .add_plugins(map::MapPlugin)
         .add_plugins(collision::CollisionPlugin)
         .add_plugins(characters::CharactersPlugin) 
-        .add_systems(Startup, (setup_camera, setup_generator))
+        .add_systems(Startup, setup_camera)
         .run();
 }
 
@@ -0,0 +1,529 @@
+// src/map/analysis.rs
+//! Consistency checks on the WFC rule set, without generating a map.
+//!
+//! A mistake in `create_sockets` or in the connections of `rules.rs` still
+//! builds fine, it only shows up as strange maps. The library keeps sockets
+//! and connections private, so instead of reading them we ask its `Rules`:
+//! to learn what may sit next to a model, we build a two-cell generator, pin
+//! the model in one cell and read what the other cell still allows. Sockets
+//! are probed the same way, through probe models added to a copy of the rules.
+//!
+//! Reported issues:
+//! - orphan sockets: a model side that no model can face (a missing
+//!   connection, or a missing rotation of an edge or corner tile)
+//! - unused sockets: created in `create_sockets`, on no model
+//! - unreachable models: no place in the layer stack, or a side nothing fits
+//! - asymmetric connections: A accepts B on one side, B refuses A back
+//!
+//! The report also totals the weights of each layer's models.
+use std::fmt;
+use std::sync::Arc;
+
+use bevy_procedural_tilemaps::prelude::*;
+use bevy_procedural_tilemaps::proc_gen::generator::{
+    model::{ALL_MODEL_ROTATIONS, ModelIndex, ModelVariantIndex},
+    rules::Rules,
+};
+
+use crate::map::{constraints::layer, generate::GRID_Z, rules::RuleSet};
+
+/// Sides of a model, in `Direction` order so a direction converts to its index here
+const SIDES: [(Direction, &str); 6] = [
+    (Direction::XForward, "right"),
+    (Direction::YForward, "up"),
+    (Direction::XBackward, "left"),
+    (Direction::YBackward, "down"),
+    (Direction::ZForward, "top"),
+    (Direction::ZBackward, "bottom"),
+];
+const ABOVE: usize = 4;
+const BELOW: usize = 5;
+
+/// What the analyzer found.
+#[derive(Debug, Clone)]
+pub struct RulesReport {
+    pub layers: Vec<LayerWeights>,
+    pub issues: Vec<RuleIssue>,
+}
+
+/// The models of one layer and the sum of their weights.
+#[derive(Debug, Clone)]
+pub struct LayerWeights {
+    pub layer: u32,
+    pub models: usize,
+    pub total_weight: f32,
+}
+
+#[derive(Debug, Clone)]
+pub enum RuleIssue {
+    /// `models` carry `socket` on their `side`, but nothing can face it there
+    OrphanSocket { socket: &'static str, side: &'static str, models: Vec<String> },
+    /// Created in `create_sockets`, used by no model
+    UnusedSocket { socket: &'static str },
+    /// The model can never be placed inside a map
+    UnreachableModel { model: String, reason: String },
+    /// `model` accepts `neighbour` on its `side`, `neighbour` doesn't accept it back
+    AsymmetricConnection { model: String, side: &'static str, neighbour: String },
+}
+
+impl RulesReport {
+    pub fn is_consistent(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+impl fmt::Display for RuleIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RuleIssue::OrphanSocket { socket, side, models } => write!(
+                f,
+                "orphan socket {socket}: nothing can face the {side} side of [{}]",
+                models.join(", ")
+            ),
+            RuleIssue::UnusedSocket { socket } => write!(f, "unused socket {socket}: no model uses it"),
+            RuleIssue::UnreachableModel { model, reason } => write!(f, "unreachable model {model}: {reason}"),
+            RuleIssue::AsymmetricConnection { model, side, neighbour } => write!(
+                f,
+                "asymmetric connection: {model} accepts {neighbour} on its {side} side, not the other way around"
+            ),
+        }
+    }
+}
+
+impl fmt::Display for RulesReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Layer weights:")?;
+        for layer in &self.layers {
+            let name = layer::NAMES.get(layer.layer as usize).copied().unwrap_or("?");
+            writeln!(
+                f,
+                "  {} {:<12} {:>3} models, total weight {:.3}",
+                layer.layer, name, layer.models, layer.total_weight
+            )?;
+        }
+
+        if self.issues.is_empty() {
+            return write!(f, "No issues found");
+        }
+        write!(f, "{} issue(s):", self.issues.len())?;
+        for issue in &self.issues {
+            write!(f, "\n  {issue}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Probe models added to the rules for one socket
+struct SocketProbe {
+    name: &'static str,
+    /// Has the socket on every side: whatever fits next to it can face the socket
+    carrier: ModelIndex,
+    /// Only connects to the socket: whatever fits next to it carries the socket
+    detector: ModelIndex,
+}
+
+/// Which variants carry each named socket on each side, and which can face it.
+/// Also used to explain generation failures (`generation::diagnose`).
+pub struct SocketConnections {
+    rules: Arc<Rules<Cartesian3D>>,
+    /// Variants of the rule set's own models, the probes' come after them
+    variant_count: usize,
+    sockets: Vec<SocketSides>,
+}
+
+struct SocketSides {
+    name: &'static str,
+    /// carriers[side]: variants with the socket on that side
+    carriers: Vec<Vec<ModelVariantIndex>>,
+    /// facing[side]: variants that fit on that side of a model carrying the socket
+    facing: Vec<Vec<ModelVariantIndex>>,
+}
+
+impl SocketConnections {
+    pub fn new(rule_set: &RuleSet) -> Self {
+        let (rules, probes) = probed_rules(rule_set);
+        let variant_count = (0..rule_set.models.models_count())
+            .flat_map(|model| ALL_MODEL_ROTATIONS.iter().map(move |&rotation| (model, rotation)))
+            .filter(|&(model, rotation)| rules.variant_index(model, rotation).is_some())
+            .count();
+        let real = |found: Vec<ModelVariantIndex>| -> Vec<ModelVariantIndex> {
+            found.into_iter().filter(|variant| *variant < variant_count).collect()
+        };
+
+        let sockets = probes
+            .iter()
+            .map(|probe| SocketSides {
+                name: probe.name,
+                carriers: SIDES
+                    .iter()
+                    .map(|&(direction, _)| {
+                        real(allowed_neighbours(&rules, (probe.detector, ModelRotation::Rot0), opposite(direction)))
+                    })
+                    .collect(),
+                facing: SIDES
+                    .iter()
+                    .map(|&(direction, _)| real(allowed_neighbours(&rules, (probe.carrier, ModelRotation::Rot0), direction)))
+                    .collect(),
+            })
+            .collect();
+
+        Self { rules, variant_count, sockets }
+    }
+
+    /// Variant index of a model as placed by a generator using the same rule set
+    pub fn variant(&self, model: ModelIndex, rotation: ModelRotation) -> Option<ModelVariantIndex> {
+        self.rules.variant_index(model, rotation).filter(|&variant| variant < self.variant_count)
+    }
+
+    /// Names of the sockets `variant` carries on its side towards `direction`
+    pub fn sockets_on(&self, variant: ModelVariantIndex, direction: Direction) -> Vec<&'static str> {
+        let side = usize::from(direction);
+        self.sockets
+            .iter()
+            .filter(|socket| socket.carriers[side].contains(&variant))
+            .map(|socket| socket.name)
+            .collect()
+    }
+
+    /// Variants that fit on the `direction` side of a model carrying `socket` there
+    pub fn facing(&self, socket: &str, direction: Direction) -> &[ModelVariantIndex] {
+        self.sockets
+            .iter()
+            .find(|sides| sides.name == socket)
+            .map_or(&[], |sides| sides.facing[usize::from(direction)].as_slice())
+    }
+}
+
+/// Check the rule set, see the module docs for what is reported.
+pub fn analyze(rule_set: &RuleSet) -> RulesReport {
+    let connections = SocketConnections::new(rule_set);
+    let rules = &connections.rules;
+
+    // The real models' variants come first, the probes' after them
+    let mut variants: Vec<(ModelVariantIndex, ModelIndex, ModelRotation)> = (0..rule_set.models.models_count())
+        .flat_map(|model| ALL_MODEL_ROTATIONS.iter().map(move |&rotation| (model, rotation)))
+        .filter_map(|(model, rotation)| Some((rules.variant_index(model, rotation)?, model, rotation)))
+        .collect();
+    variants.sort_by_key(|(variant, ..)| *variant);
+    let variant_count = variants.len();
+
+    let labels: Vec<String> = variants
+        .iter()
+        .map(|&(_, model, rotation)| variant_label(rule_set, model, rotation))
+        .collect();
+    let real = |found: Vec<ModelVariantIndex>| -> Vec<ModelVariantIndex> {
+        found.into_iter().filter(|variant| *variant < variant_count).collect()
+    };
+
+    // neighbours[variant][side]: the variants allowed on that side
+    let neighbours: Vec<Vec<Vec<ModelVariantIndex>>> = variants
+        .iter()
+        .map(|&(_, model, rotation)| {
+            SIDES
+                .iter()
+                .map(|&(direction, _)| real(allowed_neighbours(rules, (model, rotation), direction)))
+                .collect()
+        })
+        .collect();
+
+    let layers = stack_layers(&neighbours);
+    let mut issues = Vec::new();
+
+    // Sockets: who carries each one, and can anything face it there
+    for socket in &connections.sockets {
+        let mut used = false;
+        for (side, &(_, side_name)) in SIDES.iter().enumerate() {
+            let carriers = &socket.carriers[side];
+            if carriers.is_empty() {
+                continue;
+            }
+            used = true;
+
+            // Nothing goes under the bottom layer or over the top one
+            let on_grid_edge = (side == BELOW && carriers.iter().all(|&variant| layers[variant] == Some(0)))
+                || (side == ABOVE && carriers.iter().all(|&variant| layers[variant] == Some(GRID_Z - 1)));
+            if socket.facing[side].is_empty() && !on_grid_edge {
+                issues.push(RuleIssue::OrphanSocket {
+                    socket: socket.name,
+                    side: side_name,
+                    models: carriers.iter().map(|&variant| labels[variant].clone()).collect(),
+                });
+            }
+        }
+        if !used {
+            issues.push(RuleIssue::UnusedSocket { socket: socket.name });
+        }
+    }
+
+    // Models: a place in the stack, and something on every side
+    for variant in 0..variant_count {
+        let model = labels[variant].clone();
+        let Some(layer) = layers[variant] else {
+            let reason = "no stack of models from the bottom layer leads to it".to_string();
+            issues.push(RuleIssue::UnreachableModel { model, reason });
+            continue;
+        };
+        if layer >= GRID_Z {
+            let reason = format!("it sits on layer {layer}, the grid only has {GRID_Z}");
+            issues.push(RuleIssue::UnreachableModel { model, reason });
+            continue;
+        }
+
+        for (side, &(_, side_name)) in SIDES.iter().enumerate() {
+            let expected = match side {
+                BELOW => layer > 0,
+                ABOVE => layer < GRID_Z - 1,
+                _ => true,
+            };
+            if expected && neighbours[variant][side].is_empty() {
+                let reason = format!("nothing fits on its {side_name} side");
+                issues.push(RuleIssue::UnreachableModel { model: model.clone(), reason });
+            }
+        }
+    }
+
+    issues.extend(asymmetric_connections(&neighbours, &labels));
+
+    RulesReport { layers: layer_weights(rules, &layers), issues }
+}
+
+/// Connections: whatever A accepts on a side must accept A on the other.
+/// `neighbours[variant][side]` lists the variants allowed on that side.
+fn asymmetric_connections(neighbours: &[Vec<Vec<ModelVariantIndex>>], labels: &[String]) -> Vec<RuleIssue> {
+    let mut issues = Vec::new();
+    for (variant, sides) in neighbours.iter().enumerate() {
+        for (side, &(direction, side_name)) in SIDES.iter().enumerate() {
+            let back = usize::from(opposite(direction));
+            for &neighbour in &sides[side] {
+                if !neighbours[neighbour][back].contains(&variant) {
+                    issues.push(RuleIssue::AsymmetricConnection {
+                        model: labels[variant].clone(),
+                        side: side_name,
+                        neighbour: labels[neighbour].clone(),
+                    });
+                }
+            }
+        }
+    }
+    issues
+}
+
+/// Copy of the rules with a carrier and a detector model per named socket.
+fn probed_rules(rule_set: &RuleSet) -> (Arc<Rules<Cartesian3D>>, Vec<SocketProbe>) {
+    let mut models = rule_set.models.clone();
+    let mut sockets = rule_set.sockets.clone();
+
+    let probes = rule_set
+        .terrain_sockets
+        .named()
+        .into_iter()
+        .map(|(name, socket)| {
+            let carrier = models.create(SocketsCartesian3D::Mono(socket)).index();
+            let partner = sockets.create();
+            sockets.add_connection(partner, vec![socket]);
+            let detector = models.create(SocketsCartesian3D::Mono(partner)).index();
+            SocketProbe { name, carrier, detector }
+        })
+        .collect();
+
+    let rules = RulesBuilder::new_cartesian_3d(models, sockets)
+        // Same rotation axis as the game's generator
+        .with_rotation_axis(Direction::ZForward)
+        .build()
+        .expect("the rule set has models and sockets");
+
+    (Arc::new(rules), probes)
+}
+
+/// Variants the rules allow next to `pinned`, in `direction`.
+fn allowed_neighbours(
+    rules: &Arc<Rules<Cartesian3D>>,
+    pinned: (ModelIndex, ModelRotation),
+    direction: Direction,
+) -> Vec<ModelVariantIndex> {
+    // Two cells along the direction's axis, cell 1 after cell 0
+    let grid = match direction {
+        Direction::XForward | Direction::XBackward => CartesianGrid::new_cartesian_3d(2, 1, 1, false, false, false),
+        Direction::YForward | Direction::YBackward => CartesianGrid::new_cartesian_3d(1, 2, 1, false, false, false),
+        Direction::ZForward | Direction::ZBackward => CartesianGrid::new_cartesian_3d(1, 1, 2, false, false, false),
+    };
+    let forward = matches!(direction, Direction::XForward | Direction::YForward | Direction::ZForward);
+    let (pinned_cell, other_cell): (usize, usize) = if forward { (0, 1) } else { (1, 0) };
+
+    let generator = GeneratorBuilder::new()
+        .with_shared_rules(rules.clone())
+        .with_grid(grid)
+        .with_initial_nodes(vec![(pinned_cell, pinned)])
+        .and_then(|builder| builder.build());
+
+    match generator {
+        Ok(generator) => generator
+            .get_models_on(other_cell)
+            .iter()
+            .filter_map(|instance| rules.variant_index(instance.model_index, instance.rotation))
+            .collect(),
+        // Pinning it left the other cell empty
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Layer of each variant: the bottom layer has nothing below it, every
+/// other layer sits on the one under it. `None` when no stack reaches it.
+fn stack_layers(neighbours: &[Vec<Vec<ModelVariantIndex>>]) -> Vec<Option<u32>> {
+    let mut layers = vec![None; neighbours.len()];
+    let mut current: Vec<ModelVariantIndex> =
+        (0..neighbours.len()).filter(|&variant| neighbours[variant][BELOW].is_empty()).collect();
+    let mut layer = 0;
+
+    while !current.is_empty() {
+        for &variant in &current {
+            layers[variant] = Some(layer);
+        }
+        let mut next: Vec<ModelVariantIndex> = current
+            .iter()
+            .flat_map(|&variant| neighbours[variant][ABOVE].iter().copied())
+            .filter(|&above| layers[above].is_none())
+            .collect();
+        next.sort_unstable();
+        next.dedup();
+
+        current = next;
+        layer += 1;
+    }
+
+    layers
+}
+
+fn layer_weights(rules: &Rules<Cartesian3D>, layers: &[Option<u32>]) -> Vec<LayerWeights> {
+    let top = layers.iter().flatten().max().map_or(0, |&layer| layer + 1);
+    (0..top)
+        .map(|layer| {
+            let variants: Vec<ModelVariantIndex> =
+                (0..layers.len()).filter(|&variant| layers[variant] == Some(layer)).collect();
+            LayerWeights {
+                layer,
+                models: variants.len(),
+                total_weight: variants.iter().filter_map(|&variant| rules.weight(variant)).sum(),
+            }
+        })
+        .collect()
+}
+
+fn variant_label(rule_set: &RuleSet, model: ModelIndex, rotation: ModelRotation) -> String {
+    let name = rule_set
+        .model_names
+        .iter()
+        .find(|(_, index)| **index == model)
+        .map(|(name, _)| name.clone())
+        .unwrap_or_else(|| format!("model #{model}"));
+
+    match rotation {
+        ModelRotation::Rot0 => name,
+        rotation => format!("{name} ({rotation:?})"),
+    }
+}
+
+pub fn opposite(direction: Direction) -> Direction {
+    match direction {
+        Direction::XForward => Direction::XBackward,
+        Direction::XBackward => Direction::XForward,
+        Direction::YForward => Direction::YBackward,
+        Direction::YBackward => Direction::YForward,
+        Direction::ZForward => Direction::ZBackward,
+        Direction::ZBackward => Direction::ZForward,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::map::{
+        models::TerrainModelBuilder,
+        rules::build_rule_set,
+        sockets::{TerrainSockets, create_sockets},
+    };
+
+    #[test]
+    fn game_rules_are_consistent() {
+        let report = analyze(&build_rule_set());
+        assert!(report.is_consistent(), "{report}");
+    }
+
+    /// A rule set over the game's sockets, with no connections but the ones `build` adds.
+    fn rule_set(build: impl FnOnce(&TerrainSockets, &mut SocketCollection, &mut TerrainModelBuilder)) -> RuleSet {
+        let mut sockets = SocketCollection::new();
+        let terrain_sockets = create_sockets(&mut sockets);
+        let mut builder = TerrainModelBuilder::new();
+        build(&terrain_sockets, &mut sockets, &mut builder);
+
+        let (assets, models, model_names) = builder.into_parts();
+        RuleSet { assets, models, sockets, terrain_sockets, model_names }
+    }
+
+    /// The same socket on every side but `z_pos` and `z_neg`
+    fn column(side: Socket, z_pos: Socket, z_neg: Socket) -> SocketsCartesian3D {
+        SocketsCartesian3D::Simple { x_pos: side, x_neg: side, y_pos: side, y_neg: side, z_pos, z_neg }
+    }
+
+    #[test]
+    fn a_socket_nothing_connects_to_is_an_orphan() {
+        let report = analyze(&rule_set(|terrain, sockets, builder| {
+            let (side, bottom) = (sockets.create(), sockets.create());
+            sockets.add_connection(side, vec![side]);
+            // dirt.layer_up is connected to nothing
+            builder.create_named_model("ground", column(side, terrain.dirt.layer_up, bottom), vec![]);
+        }));
+
+        assert!(
+            report.issues.iter().any(|issue| matches!(
+                issue,
+                RuleIssue::OrphanSocket { socket: "dirt.layer_up", side: "top", models } if models == &["ground"]
+            )),
+            "{report}"
+        );
+    }
+
+    #[test]
+    fn a_model_no_stack_reaches_is_unreachable() {
+        let report = analyze(&rule_set(|_, sockets, builder| {
+            let (side, top, bottom) = (sockets.create(), sockets.create(), sockets.create());
+            sockets.add_connection(side, vec![side]);
+            builder.create_named_model("ground", column(side, top, bottom), vec![]);
+
+            // Only ever stacks on itself, so something is always below it but
+            // no column starting on the ground leads to it
+            let (floating_top, floating_bottom) = (sockets.create(), sockets.create());
+            sockets.add_connection(floating_top, vec![floating_bottom]);
+            builder.create_named_model("floating", column(side, floating_top, floating_bottom), vec![]);
+        }));
+
+        assert!(
+            report.issues.iter().any(|issue| matches!(
+                issue,
+                RuleIssue::UnreachableModel { model, reason } if model == "floating" && reason.contains("no stack")
+            )),
+            "{report}"
+        );
+    }
+
+    #[test]
+    fn a_one_way_connection_is_asymmetric() {
+        // Socket connections always go both ways, so a one-way connection can't
+        // be built from sockets; it would come from a library regression. Check
+        // the neighbour table itself: "a" accepts "b" on its right, "b" doesn't
+        // accept "a" on its left.
+        let mut neighbours = vec![vec![Vec::new(); SIDES.len()]; 2];
+        neighbours[0][usize::from(Direction::XForward)] = vec![1];
+        let labels = ["a".to_string(), "b".to_string()];
+
+        let issues = asymmetric_connections(&neighbours, &labels);
+
+        assert!(matches!(
+            issues.as_slice(),
+            [RuleIssue::AsymmetricConnection { model, side: "right", neighbour }] if model == "a" && neighbour == "b"
+        ));
+
+        neighbours[1][usize::from(Direction::XBackward)] = vec![0];
+        assert!(asymmetric_connections(&neighbours, &labels).is_empty());
+    }
+}
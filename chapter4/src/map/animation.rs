@@ -0,0 +1,142 @@
+// src/map/animation.rs
+use std::sync::Arc;
+
+use bevy::prelude::*;
+
+use crate::map::generate::TILE_SIZE;
+
+/// Cycles a tile's atlas index through a list of frames.
+/// Added by `assets::tile_bundle` for sprites that have a `TilemapAnimation`.
+#[derive(Component, Debug, Clone)]
+pub struct AnimatedTile {
+    /// Atlas index of the animated sprite itself, what exports save
+    pub base: usize,
+    /// Atlas indices, in playback order
+    pub frames: Arc<[usize]>,
+    /// Seconds per frame
+    pub frame_time: f32,
+}
+
+impl AnimatedTile {
+    pub fn new(base: usize, frames: impl Into<Arc<[usize]>>, frame_time: f32) -> Self {
+        Self {
+            base,
+            frames: frames.into(),
+            frame_time,
+        }
+    }
+
+    /// Atlas index to show `elapsed` seconds in, for a tile `phase` (0..1) of
+    /// a cycle ahead of the others. `None` when there is nothing to play.
+    pub fn frame_at(&self, elapsed: f32, phase: f32) -> Option<usize> {
+        let frame_count = self.frames.len();
+        if frame_count == 0 || self.frame_time <= 0.0 {
+            return None;
+        }
+
+        let cycle = self.frame_time * frame_count as f32;
+        let t = elapsed + phase * cycle;
+        let frame = (t / self.frame_time) as usize % frame_count;
+        Some(self.frames[frame])
+    }
+}
+
+/// Per-cell time offset in [0, 1) of a full cycle, so neighbouring
+/// tiles don't animate in lockstep.
+fn cell_phase(position: Vec3) -> f32 {
+    let x = (position.x / TILE_SIZE).floor() as i32 as u32;
+    let y = (position.y / TILE_SIZE).floor() as i32 as u32;
+
+    // Cheap integer hash of the cell coordinates
+    let mut hash = x.wrapping_mul(0x9E37_79B9) ^ y.wrapping_mul(0x85EB_CA6B);
+    hash ^= hash >> 15;
+    hash = hash.wrapping_mul(0x2C1B_3C6D);
+    hash ^= hash >> 12;
+
+    (hash & 0xFFFF) as f32 / 65536.0
+}
+
+pub fn animate_tiles(
+    time: Res<Time>,
+    mut query: Query<(&AnimatedTile, &Transform, &mut Sprite)>,
+) {
+    let elapsed = time.elapsed_secs();
+
+    for (animation, transform, mut sprite) in query.iter_mut() {
+        // Tiles never move, so the phase from their position is stable
+        let Some(index) = animation.frame_at(elapsed, cell_phase(transform.translation)) else {
+            continue;
+        };
+
+        if let Some(atlas) = sprite.texture_atlas.as_mut()
+            && atlas.index != index
+        {
+            atlas.index = index;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+
+    #[test]
+    fn cell_phase_is_per_cell_and_within_a_cycle() {
+        let cell = Vec3::new(3.0 * TILE_SIZE, -2.0 * TILE_SIZE, 0.0);
+        let phase = cell_phase(cell);
+
+        assert!((0.0..1.0).contains(&phase));
+        // Anywhere in the same cell, at any depth
+        assert_eq!(cell_phase(cell + Vec3::new(TILE_SIZE * 0.9, TILE_SIZE * 0.5, 4.0)), phase);
+
+        let phases: Vec<f32> = (0..8).map(|x| cell_phase(Vec3::new(x as f32 * TILE_SIZE, 0.0, 0.0))).collect();
+        assert!(phases.windows(2).any(|pair| pair[0] != pair[1]), "neighbouring cells animate in lockstep");
+    }
+
+    #[test]
+    fn frames_follow_time_and_phase() {
+        let animation = AnimatedTile::new(10, vec![10, 11, 12], 0.5);
+
+        assert_eq!(animation.frame_at(0.0, 0.0), Some(10));
+        assert_eq!(animation.frame_at(0.6, 0.0), Some(11));
+        assert_eq!(animation.frame_at(1.2, 0.0), Some(12));
+        // Wraps around after a 1.5s cycle
+        assert_eq!(animation.frame_at(1.6, 0.0), Some(10));
+        // A third of a cycle ahead is one frame ahead
+        assert_eq!(animation.frame_at(0.1, 1.0 / 3.0), Some(11));
+
+        assert_eq!(AnimatedTile::new(10, vec![], 0.5).frame_at(1.0, 0.0), None);
+        assert_eq!(AnimatedTile::new(10, vec![10, 11], 0.0).frame_at(1.0, 0.0), None);
+    }
+
+    #[test]
+    fn animate_tiles_sets_the_atlas_index() {
+        let mut world = World::new();
+        let mut time = Time::<()>::default();
+        time.advance_by(Duration::from_secs_f32(0.6));
+        world.insert_resource(time);
+
+        let animation = AnimatedTile::new(10, vec![10, 11, 12], 0.5);
+        let transform = Transform::from_xyz(TILE_SIZE, 0.0, 0.0);
+        let expected = animation.frame_at(0.6, cell_phase(transform.translation)).unwrap();
+        let tile = world
+            .spawn((
+                animation,
+                transform,
+                Sprite {
+                    texture_atlas: Some(TextureAtlas { layout: Handle::default(), index: 10 }),
+                    ..default()
+                },
+            ))
+            .id();
+
+        world.run_system_once(animate_tiles).unwrap();
+
+        let sprite = world.get::<Sprite>(tile).unwrap();
+        assert_eq!(sprite.texture_atlas.as_ref().unwrap().index, expected);
+    }
+}
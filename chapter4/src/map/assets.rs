@@ -1,8 +1,16 @@
+use std::sync::Arc;
+
 use bevy::{prelude::*};
 use bevy_procedural_tilemaps::prelude::*;
 use crate::collision::{TileMarker, TileType};
-use crate::map::tilemap::TILEMAP; // <--- line update alert
+use crate::map::tilemap::{TilemapAnimation, TilemapDefinition, TilemapError}; // <--- line update alert
+use crate::map::occlusion::Occluder;
+use crate::map::animation::AnimatedTile;
+
 
+/// Runs on a freshly spawned tile entity to add gameplay components
+/// (collision markers, interactables, lights...).
+pub type TileHook = Arc<dyn Fn(&mut EntityCommands) + Send + Sync>;
 
 #[derive(Clone)]
 pub struct SpawnableAsset {
@@ -12,8 +20,10 @@ pub struct SpawnableAsset {
     grid_offset: GridDelta,
     /// Offset in world coordinates (fine positioning)
     offset: Vec3,
-    /// Function to add custom components (like collision, physics, etc.)
+    /// Collision type, spawned as a `TileMarker`
     tile_type: Option<TileType>,
+    /// Hooks that add custom components (like physics, interaction, etc.)
+    hooks: Vec<TileHook>,
 }
 
 impl SpawnableAsset {
@@ -22,7 +32,8 @@ impl SpawnableAsset {
             sprite_name,
             grid_offset: GridDelta::new(0, 0, 0),
             offset: Vec3::ZERO,
-            tile_type: None, // Default: no extra components
+            tile_type: None,
+            hooks: Vec::new(), // Default: no extra components
         }
     }
 
@@ -31,72 +42,137 @@ impl SpawnableAsset {
         self
     }
 
-    pub fn with_tile_type(mut self, tile_type: TileType) -> Self { 
+    pub fn with_tile_type(mut self, tile_type: TileType) -> Self {
         self.tile_type = Some(tile_type);
         self
     }
+
+    /// Fade this sprite when it covers the player (tree tops, canopies).
+    pub fn with_occlusion_fade(self) -> Self {
+        self.with_component(Occluder::default())
+    }
+
+    /// Add a component to every tile spawned from this asset.
+    pub fn with_component<C: Component + Clone>(self, component: C) -> Self {
+        self.with_spawn_hook(move |e: &mut EntityCommands| {
+            e.insert(component.clone());
+        })
+    }
+
+    /// Run custom code on every tile spawned from this asset, for anything
+    /// a single component can't express (bundles, observers, child entities).
+    pub fn with_spawn_hook(mut self, hook: impl Fn(&mut EntityCommands) + Send + Sync + 'static) -> Self {
+        self.hooks.push(Arc::new(hook));
+        self
+    }
+
+    pub fn sprite_name(&self) -> &'static str {
+        self.sprite_name
+    }
+
+    pub fn grid_offset(&self) -> GridDelta {
+        self.grid_offset
+    }
+
+    pub fn tile_type(&self) -> Option<TileType> {
+        self.tile_type
+    }
+
+    pub fn hooks(&self) -> &[TileHook] {
+        &self.hooks
+    }
+}
+
+/// What the generator spawns for each tile: the atlas sprite plus the
+/// asset's spawn hooks.
+#[derive(Clone, Default)]
+pub struct TileBundle {
+    sprite: Sprite,
+    hooks: Vec<TileHook>,
+}
+
+impl BundleInserter for TileBundle {
+    fn insert_bundle(
+        &self,
+        commands: &mut EntityCommands,
+        translation: Vec3,
+        scale: Vec3,
+        rotation: ModelRotation,
+    ) {
+        self.sprite.insert_bundle(commands, translation, scale, rotation);
+        for hook in &self.hooks {
+            hook(commands);
+        }
+    }
 }
 
 #[derive(Clone)]
 pub struct TilemapHandles {
     pub image: Handle<Image>,
     pub layout: Handle<TextureAtlasLayout>,
+    /// Tint of each atlas sprite, by atlas index
+    pub tints: Vec<Color>,
 }
 
 impl TilemapHandles {
     pub fn sprite(&self, atlas_index: usize) -> Sprite {
-        Sprite::from_atlas_image(
-            self.image.clone(),
-            TextureAtlas::from(self.layout.clone()).with_index(atlas_index),
-        )
+        Sprite {
+            color: self.tints[atlas_index],
+            ..Sprite::from_atlas_image(
+                self.image.clone(),
+                TextureAtlas::from(self.layout.clone()).with_index(atlas_index),
+            )
+        }
     }
 }
 
 pub fn prepare_tilemap_handles(
+    tilemap: &TilemapDefinition,
     asset_server: &Res<AssetServer>,
     atlas_layouts: &mut ResMut<Assets<TextureAtlasLayout>>,
     assets_directory: &str,
     tilemap_file: &str,
 ) -> TilemapHandles {
     let image = asset_server.load::<Image>(format!("{assets_directory}/{tilemap_file}"));
-    let mut layout = TextureAtlasLayout::new_empty(TILEMAP.atlas_size());
-    for index in 0..TILEMAP.sprites.len() {
-        layout.add_texture(TILEMAP.sprite_rect(index));
+    let mut layout = TextureAtlasLayout::new_empty(tilemap.atlas_size());
+    for index in 0..tilemap.sprites.len() {
+        layout.add_texture(tilemap.sprite_rect(index));
     }
     let layout = atlas_layouts.add(layout);
+    let tints = (0..tilemap.sprites.len()).map(|index| tilemap.sprite_tint(index)).collect();
 
-    TilemapHandles { image, layout }
+    TilemapHandles { image, layout, tints }
 }
 
 pub fn load_assets(
+    tilemap: &TilemapDefinition,
     tilemap_handles: &TilemapHandles,
     assets_definitions: Vec<Vec<SpawnableAsset>>,
-) -> ModelsAssets<Sprite> {
-    let mut models_assets = ModelsAssets::<Sprite>::new();
+) -> ModelsAssets<TileBundle> {
+    let mut models_assets = ModelsAssets::<TileBundle>::new();
     
     for (model_index, assets) in assets_definitions.into_iter().enumerate() {
         for asset_def in assets {
-            let SpawnableAsset {
-                sprite_name,
-                grid_offset,
-                offset,
-                tile_type, // Line update alert
-            } = asset_def;
-
-            let Some(atlas_index) = TILEMAP.sprite_index(sprite_name) else {
-                panic!("Unknown atlas sprite '{}'", sprite_name);
+            let context = format!("model {model_index}");
+            let Some(assets_bundle) = tile_bundle(
+                tilemap,
+                tilemap_handles,
+                asset_def.sprite_name,
+                asset_def.tile_type,
+                &asset_def.hooks,
+                &context,
+            ) else {
+                continue;
             };
 
-            // Create the spawner function that adds components
-            let spawner = create_spawner(tile_type); // Line update alert
-
             models_assets.add(
                 model_index,
                 ModelAsset {
-                    assets_bundle: tilemap_handles.sprite(atlas_index),
-                    grid_offset,
-                    world_offset: offset,
-                    spawn_commands: spawner, 
+                    assets_bundle,
+                    grid_offset: asset_def.grid_offset,
+                    world_offset: asset_def.offset,
+                    // Components come from the bundle's hooks instead
+                    spawn_commands: |_: &mut EntityCommands| {},
                 },
             );
         }
@@ -104,36 +180,65 @@ pub fn load_assets(
     models_assets
 }
 
-fn create_spawner(
+/// Everything a tile spawns with, whether the generator or a Tiled import
+/// places it: the collision marker first, then the asset's own hooks, then
+/// the sprite's animation. `context` says where the sprite came from in errors.
+pub fn tile_bundle(
+    tilemap: &TilemapDefinition,
+    tilemap_handles: &TilemapHandles,
+    sprite_name: &str,
     tile_type: Option<TileType>,
-) -> fn(&mut EntityCommands) {
-    match tile_type {
-        // Tile types without pickable
-        Some(TileType::Dirt) => |e: &mut EntityCommands| {
-            e.insert(TileMarker::new(TileType::Dirt));
-        },
-        Some(TileType::Grass) => |e: &mut EntityCommands| {
-            e.insert(TileMarker::new(TileType::Grass));
-        },
-        Some(TileType::YellowGrass) => |e: &mut EntityCommands| {
-            e.insert(TileMarker::new(TileType::YellowGrass));
-        },
-        Some(TileType::Water) => |e: &mut EntityCommands| {
-            e.insert(TileMarker::new(TileType::Water));
-        },
-        Some(TileType::Shore) => |e: &mut EntityCommands| {
-            e.insert(TileMarker::new(TileType::Shore));
-        },
-        Some(TileType::Tree) => |e: &mut EntityCommands| {
-            e.insert(TileMarker::new(TileType::Tree));
-        },
-        Some(TileType::Rock) => |e: &mut EntityCommands| {
-            e.insert(TileMarker::new(TileType::Rock));
-        },
-        Some(TileType::Empty) => |e: &mut EntityCommands| {
-            e.insert(TileMarker::new(TileType::Empty));
-        },
-        // Default: no components
-        _ => |_: &mut EntityCommands| {},
-    }
-}
\ No newline at end of file
+    asset_hooks: &[TileHook],
+    context: &str,
+) -> Option<TileBundle> {
+    // A typo shouldn't take the whole map down: skip the sprite and say so
+    let Some(atlas_index) = tilemap.sprite_index(sprite_name) else {
+        error!("Unknown atlas sprite '{sprite_name}' in {context}, skipping it");
+        return None;
+    };
+
+    let mut hooks: Vec<TileHook> = Vec::with_capacity(asset_hooks.len() + 2);
+
+    // Collision marker goes on before any custom components
+    if let Some(tile_type) = tile_type {
+        hooks.push(Arc::new(move |e: &mut EntityCommands| {
+            e.insert(TileMarker::new(tile_type));
+        }));
+    }
+    hooks.extend(asset_hooks.iter().cloned());
+
+    // Animated sprites cycle through their frames at runtime
+    match tilemap.animation(sprite_name).map(|animation| animated_tile(tilemap, atlas_index, animation)) {
+        Some(Ok(animated)) => hooks.push(Arc::new(move |e: &mut EntityCommands| {
+            e.insert(animated.clone());
+        })),
+        Some(Err(err)) => error!("{err}, keeping the sprite static in {context}"),
+        None => {}
+    }
+
+    Some(TileBundle {
+        sprite: tilemap_handles.sprite(atlas_index),
+        hooks,
+    })
+}
+
+/// Frame names are checked when the atlas loads, but a definition built in
+/// code skips that, so a missing frame is still reported rather than dropped.
+fn animated_tile(
+    tilemap: &TilemapDefinition,
+    base: usize,
+    animation: &TilemapAnimation,
+) -> Result<AnimatedTile, TilemapError> {
+    let frames = animation
+        .frames
+        .iter()
+        .map(|frame| {
+            tilemap.sprite_index(frame).ok_or_else(|| TilemapError::UnknownFrame {
+                animation: animation.sprite.clone(),
+                frame: frame.clone(),
+            })
+        })
+        .collect::<Result<Vec<usize>, _>>()?;
+
+    Ok(AnimatedTile::new(base, frames, animation.frame_time))
+}
@@ -0,0 +1,208 @@
+// src/map/biomes.rs
+//! Biomes: which ground each region of the map gets.
+//!
+//! A seeded noise field assigns a biome to every cell. The biome's ground
+//! model is then pinned on the dirt layer (see `constraints`) and the WFC
+//! builds the rest of the map on top. The ground's sockets decide what can
+//! grow above it (see `rules::build_biome_grounds`): no grass, water only in
+//! wet biomes, and only the biome's own props.
+use bevy::prelude::*;
+
+use crate::collision::TileType;
+use crate::config::biome::{COLD, HOT, NOISE_SCALE, WET};
+use crate::map::constraints::{PinnedRegion, layer};
+
+/// A single-cell prop that grows on one biome's ground.
+pub struct BiomeProp {
+    pub sprite: &'static str,
+    pub tile_type: TileType,
+    /// Drawn one cell up and faded when it covers the player (tree tops)
+    pub top: Option<&'static str>,
+    pub weight: f32,
+}
+
+impl BiomeProp {
+    const fn new(sprite: &'static str, tile_type: TileType, weight: f32) -> Self {
+        Self { sprite, tile_type, top: None, weight }
+    }
+
+    const fn tree(bottom: &'static str, top: &'static str, weight: f32) -> Self {
+        Self { sprite: bottom, tile_type: TileType::Tree, top: Some(top), weight }
+    }
+}
+
+/// A few rocks and dead wood in the dunes
+const DESERT_PROPS: &[BiomeProp] = &[
+    BiomeProp::new("rock_1", TileType::Rock, 0.006),
+    BiomeProp::new("rock_2", TileType::Rock, 0.006),
+    BiomeProp::new("tree_stump_3", TileType::Stump, 0.002),
+];
+
+/// Lone trees among boulders
+const SNOWFIELD_PROPS: &[BiomeProp] = &[
+    BiomeProp::tree("small_tree_bottom", "small_tree_top", 0.02),
+    BiomeProp::new("rock_3", TileType::Rock, 0.012),
+    BiomeProp::new("rock_4", TileType::Rock, 0.012),
+    BiomeProp::new("tree_stump_1", TileType::Stump, 0.004),
+];
+
+/// Thick undergrowth and rotting stumps
+const SWAMP_PROPS: &[BiomeProp] = &[
+    BiomeProp::new("plant_1", TileType::Grass, 0.04),
+    BiomeProp::new("plant_2", TileType::Grass, 0.04),
+    BiomeProp::new("plant_3", TileType::Grass, 0.04),
+    BiomeProp::new("plant_4", TileType::Grass, 0.04),
+    BiomeProp::new("tree_stump_2", TileType::Stump, 0.015),
+    BiomeProp::new("tree_stump_3", TileType::Stump, 0.015),
+    BiomeProp::tree("small_tree_bottom", "small_tree_top", 0.008),
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Biome {
+    Grassland,
+    Desert,
+    Snowfield,
+    Swamp,
+}
+
+impl Biome {
+    /// Name of the biome's ground model, on the dirt layer
+    pub const fn ground(self) -> &'static str {
+        match self {
+            Biome::Grassland => "dirt",
+            Biome::Desert => "sand",
+            Biome::Snowfield => "snow",
+            Biome::Swamp => "mud",
+        }
+    }
+
+    /// Whether lakes can form
+    pub const fn has_water(self) -> bool {
+        matches!(self, Biome::Grassland | Biome::Swamp)
+    }
+
+    /// Props growing on the biome's ground. Grassland has the props
+    /// layer's own set (see `rules::build_props_layer`).
+    pub const fn props(self) -> &'static [BiomeProp] {
+        match self {
+            Biome::Grassland => &[],
+            Biome::Desert => DESERT_PROPS,
+            Biome::Snowfield => SNOWFIELD_PROPS,
+            Biome::Swamp => SWAMP_PROPS,
+        }
+    }
+
+    /// Pick a biome from temperature and moisture, both in 0..1.
+    fn from_climate(temperature: f32, moisture: f32) -> Self {
+        if temperature < COLD {
+            Biome::Snowfield
+        } else if temperature > HOT {
+            Biome::Desert
+        } else if moisture > WET {
+            Biome::Swamp
+        } else {
+            Biome::Grassland
+        }
+    }
+}
+
+/// The biome of every cell of the map, rows bottom-up.
+#[derive(Debug, Clone)]
+pub struct BiomeMap {
+    pub width: u32,
+    pub height: u32,
+    cells: Vec<Biome>,
+}
+
+impl BiomeMap {
+    pub fn generate(seed: u64, width: u32, height: u32) -> Self {
+        let mut cells = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                let (x, y) = (x as f32 / NOISE_SCALE, y as f32 / NOISE_SCALE);
+                let temperature = fractal_noise(seed, x, y);
+                let moisture = fractal_noise(seed ^ 0x9e37_79b9_7f4a_7c15, x, y);
+                cells.push(Biome::from_climate(temperature, moisture));
+            }
+        }
+
+        let mut map = Self { width, height, cells };
+        map.separate_biomes();
+        map
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Biome {
+        self.cells[(x + y * self.width) as usize]
+    }
+
+    /// Only grassland borders every biome, so turn cells touching a different
+    /// non-grassland biome into grassland (both sides of the border).
+    fn separate_biomes(&mut self) {
+        let original = self.cells.clone();
+        let at = |x: i32, y: i32| {
+            (x >= 0 && y >= 0 && x < self.width as i32 && y < self.height as i32)
+                .then(|| original[(x + y * self.width as i32) as usize])
+        };
+
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                let biome = original[(x + y * self.width as i32) as usize];
+                if biome == Biome::Grassland {
+                    continue;
+                }
+
+                let clashes = [(x - 1, y), (x + 1, y), (x, y - 1), (x, y + 1)]
+                    .into_iter()
+                    .filter_map(|(nx, ny)| at(nx, ny))
+                    .any(|other| other != biome && other != Biome::Grassland);
+                if clashes {
+                    self.cells[(x + y * self.width as i32) as usize] = Biome::Grassland;
+                }
+            }
+        }
+    }
+
+    /// Pins that lay the biomes out: each cell's ground on the dirt layer.
+    pub fn pins(&self) -> Vec<PinnedRegion> {
+        let mut pins = Vec::new();
+        for y in 0..self.height {
+            for x in 0..self.width {
+                pins.push(PinnedRegion::cell(self.get(x, y).ground(), layer::DIRT, x, y));
+            }
+        }
+        pins
+    }
+}
+
+/// Pseudo-random value in 0..1 for a lattice point.
+fn lattice(seed: u64, x: i32, y: i32) -> f32 {
+    let mut h = seed ^ (x as u32 as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15) ^ (y as u32 as u64).wrapping_mul(0xc2b2_ae3d_27d4_eb4f);
+    h ^= h >> 33;
+    h = h.wrapping_mul(0xff51_afd7_ed55_8ccd);
+    h ^= h >> 33;
+    (h >> 40) as f32 / (1u64 << 24) as f32
+}
+
+/// Smoothly interpolated value noise, in 0..1.
+fn value_noise(seed: u64, x: f32, y: f32) -> f32 {
+    let (x0, y0) = (x.floor(), y.floor());
+    let (tx, ty) = (x - x0, y - y0);
+    let (sx, sy) = (tx * tx * (3.0 - 2.0 * tx), ty * ty * (3.0 - 2.0 * ty));
+    let (ix, iy) = (x0 as i32, y0 as i32);
+
+    let bottom = lattice(seed, ix, iy).lerp(lattice(seed, ix + 1, iy), sx);
+    let top = lattice(seed, ix, iy + 1).lerp(lattice(seed, ix + 1, iy + 1), sx);
+    bottom.lerp(top, sy)
+}
+
+/// Three octaves of value noise, in 0..1.
+fn fractal_noise(seed: u64, x: f32, y: f32) -> f32 {
+    let (mut sum, mut amplitude, mut frequency, mut total) = (0.0, 1.0, 1.0, 0.0);
+    for octave in 0..3 {
+        sum += value_noise(seed.wrapping_add(octave), x * frequency, y * frequency) * amplitude;
+        total += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    sum / total
+}
@@ -0,0 +1,117 @@
+// src/map/constraints.rs
+//! Hand-authored regions the WFC must respect.
+//!
+//! Each region pins one model (by name, see `TerrainModelBuilder`) on every
+//! cell of a rectangle of one layer. The generator places pinned cells before
+//! anything else and fills the rest of the map around them, so they work like
+//! a level designer's stencil on top of procedural generation.
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use bevy_procedural_tilemaps::prelude::*;
+use bevy_procedural_tilemaps::proc_gen::generator::model::ModelIndex;
+
+use crate::map::{
+    generate::{GRID_X, GRID_Y},
+    models::ModelNames,
+};
+
+/// Z index of each terrain layer in the grid, bottom to top
+pub mod layer {
+    pub const DIRT: u32 = 0;
+    pub const GRASS: u32 = 1;
+    pub const YELLOW_GRASS: u32 = 2;
+    pub const WATER: u32 = 3;
+    pub const PROPS: u32 = 4;
+
+    /// Layer names by Z index, for reports
+    pub const NAMES: [&str; 5] = ["dirt", "grass", "yellow_grass", "water", "props"];
+}
+
+/// Pin `model` on every cell from `min` to `max` (inclusive) of `layer`.
+#[derive(Debug, Clone, Copy)]
+pub struct PinnedRegion {
+    pub model: &'static str,
+    pub layer: u32,
+    pub min: UVec2,
+    pub max: UVec2,
+}
+
+impl PinnedRegion {
+    pub const fn cell(model: &'static str, layer: u32, x: u32, y: u32) -> Self {
+        Self::rect(model, layer, x, y, x, y)
+    }
+
+    pub const fn rect(model: &'static str, layer: u32, min_x: u32, min_y: u32, max_x: u32, max_y: u32) -> Self {
+        Self {
+            model,
+            layer,
+            min: UVec2::new(min_x, min_y),
+            max: UVec2::new(max_x, max_y),
+        }
+    }
+
+    /// Every cell of the rectangle, row by row.
+    fn cells(&self) -> impl Iterator<Item = UVec2> + '_ {
+        (self.min.y..=self.max.y).flat_map(move |y| (self.min.x..=self.max.x).map(move |x| UVec2::new(x, y)))
+    }
+}
+
+/// Cell the player spawns on (world origin, the map is centered)
+const SPAWN: UVec2 = UVec2::new(GRID_X / 2, GRID_Y / 2);
+
+/// A dirt clearing around the spawn point: nothing but dirt on the 3x3 cells
+/// around the player, so they never start inside a tree or a lake.
+const SPAWN_CLEARING: [PinnedRegion; 5] = {
+    let (min_x, min_y, max_x, max_y) = (SPAWN.x - 1, SPAWN.y - 1, SPAWN.x + 1, SPAWN.y + 1);
+    [
+        PinnedRegion::rect("dirt", layer::DIRT, min_x, min_y, max_x, max_y),
+        PinnedRegion::rect("grass_void", layer::GRASS, min_x, min_y, max_x, max_y),
+        PinnedRegion::rect("yellow_grass_void", layer::YELLOW_GRASS, min_x, min_y, max_x, max_y),
+        PinnedRegion::rect("water_void", layer::WATER, min_x, min_y, max_x, max_y),
+        PinnedRegion::rect("props_void", layer::PROPS, min_x, min_y, max_x, max_y),
+    ]
+};
+
+/// The regions pinned in the game's map. Later regions win where they overlap.
+///
+/// More examples:
+/// - a lake in the bottom-left corner:
+///   `PinnedRegion::rect("water", layer::WATER, 1, 1, 3, 3)`
+/// - a row of trees along the top edge:
+///   `PinnedRegion::rect("small_tree_bottom", layer::PROPS, 0, GRID_Y - 2, GRID_X - 1, GRID_Y - 2)`
+pub const PINNED_REGIONS: &[PinnedRegion] = &SPAWN_CLEARING;
+
+/// A grid cell (x, y, layer) and the model variant pinned on it
+pub type PinnedNode = ((u32, u32, u32), (ModelIndex, ModelRotation));
+
+/// Resolve regions to the generator's initial nodes, skipping unknown models
+/// and cells outside the grid.
+pub fn pinned_nodes(
+    regions: &[PinnedRegion],
+    names: &ModelNames,
+    grid: &CartesianGrid<Cartesian3D>,
+) -> Vec<PinnedNode> {
+    let mut nodes = BTreeMap::new();
+
+    for region in regions {
+        let Some(&model_index) = names.get(region.model) else {
+            warn!("Pinned region uses unknown model '{}', skipping it", region.model);
+            continue;
+        };
+
+        for cell in region.cells() {
+            if cell.x >= grid.size_x() || cell.y >= grid.size_y() || region.layer >= grid.size_z() {
+                warn!(
+                    "Pinned '{}' at ({}, {}, {}) is outside the grid, skipping it",
+                    region.model, cell.x, cell.y, region.layer
+                );
+                continue;
+            }
+            // Models are declared unrotated, every rotation is its own model
+            nodes.insert((cell.x, cell.y, region.layer), (model_index, ModelRotation::Rot0));
+        }
+    }
+
+    nodes.into_iter().collect()
+}
@@ -0,0 +1,54 @@
+// src/map/depth.rs
+use bevy::prelude::*;
+
+use crate::characters::jump::JumpArc;
+use crate::map::generate::{GRID_Y, GRID_Z, NODE_SIZE, TILE_SIZE};
+
+/// Sort a sprite against the tilemap by where its feet touch the ground.
+/// Works for any moving entity (player, NPCs, items, projectiles).
+#[derive(Component, Debug, Clone, Copy, Default)]
+pub struct YSort {
+    /// Distance from the entity's origin down to its feet, in world units
+    pub feet_offset: f32,
+}
+
+impl YSort {
+    pub fn new(feet_offset: f32) -> Self {
+        Self { feet_offset }
+    }
+}
+
+/// The Z a `NodesSpawner` with `with_z_offset_from_y(true)` gives a node on `layer`
+/// at world height `y`. Tiles only use whole rows; this is the same formula with a
+/// continuous row so moving sprites slot in between them.
+pub fn z_from_y(layer: u32, y: f32) -> f32 {
+    // Generator origin, see setup_generator
+    let map_y0 = -TILE_SIZE * GRID_Y as f32 / 2.0;
+
+    // Fractional grid row, clamped to the map
+    let row = ((y - map_y0) / NODE_SIZE.y).clamp(0.0, GRID_Y as f32);
+
+    // Layer centre, plus the Y offset:
+    // lower on screen = smaller row = larger Z = rendered in front
+    NODE_SIZE.z * (layer as f32 + 0.5) + NODE_SIZE.z * (1.0 - row / GRID_Y as f32)
+}
+
+/// Entities that moved, or just started sorting.
+type YSortFilter = Or<(Changed<Transform>, Added<YSort>)>;
+
+/// Set Z for every `YSort` entity, as if it stood on the top (props) layer.
+/// Runs after movement and interpolation so it uses the rendered position.
+pub fn update_y_sort_depth(
+    mut query: Query<(&mut Transform, &YSort, Option<&JumpArc>), YSortFilter>,
+) {
+    for (mut transform, y_sort, jump) in query.iter_mut() {
+        // Sort by the ground position, not the jump offset
+        let jump_height = jump.map_or(0.0, |jump| jump.rendered_height);
+        let feet_y = transform.translation.y - jump_height - y_sort.feet_offset;
+
+        let z = z_from_y(GRID_Z - 1, feet_y);
+        if transform.translation.z != z {
+            transform.translation.z = z;
+        }
+    }
+}
@@ -0,0 +1,371 @@
+// src/map/export.rs
+//! Save the current map so designers can polish it in Tiled and load it back
+//! (see `config::map::TILED_MAP`).
+//!
+//! Press F5 while playing to write `<EXPORT_DIR>/<EXPORT_NAME>.tmx` and `.json`.
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::collision::{TileMarker, TileType};
+use crate::config::map::{EXPORT_DIR, EXPORT_NAME};
+use crate::map::{
+    animation::AnimatedTile,
+    generate::{ASSETS_PATH, GRID_X, GRID_Y, MapRoot, NODE_SIZE, TILEMAP_FILE, TilemapResource},
+    tiled::{TiledMap, TiledTile},
+    tilemap::TilemapDefinition,
+};
+
+/// Compact JSON form: each distinct (sprite, tile type) pair is stored once in
+/// `palette`, layers hold 1-based palette indices (0 = empty), rows bottom to top.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorldJson {
+    pub width: u32,
+    pub height: u32,
+    pub tile_size: u32,
+    pub palette: Vec<PaletteEntry>,
+    pub layers: Vec<JsonLayer>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct PaletteEntry {
+    pub sprite: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tile_type: Option<String>,
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub row_offset: u32,
+}
+
+fn is_zero(value: &u32) -> bool {
+    *value == 0
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonLayer {
+    pub z: u32,
+    pub tiles: Vec<u16>,
+}
+
+/// Z layer of a spawned tile, undoing the `with_z_offset_from_y` offset.
+/// The offset is in (0, 1], so the layer is the whole part just below `z - 0.5`.
+fn layer_from_z(z: f32) -> u32 {
+    ((z / NODE_SIZE.z - 0.5 - 1e-3).ceil() - 1.0).max(0.0) as u32
+}
+
+/// Row a spawned tile depth-sorts with, read back from the Y offset in its `z`
+/// (see `depth::z_from_y`). Differs from its own row for tree tops.
+fn depth_row_from_z(z: f32, layer: u32, height: u32) -> u32 {
+    let offset = z / NODE_SIZE.z - 0.5 - layer as f32;
+    ((1.0 - offset) * height as f32).round().max(0.0) as u32
+}
+
+/// A spawned tile as the exporter sees it.
+pub type CapturedTile<'a> = (&'a Sprite, &'a Transform, Option<&'a TileMarker>, Option<&'a AnimatedTile>);
+
+/// Read the map's spawned tiles back into sprite names and tile types.
+/// Animated tiles are saved as their animated sprite, not the frame showing.
+pub fn capture_world<'a>(tilemap: &TilemapDefinition, tiles: impl IntoIterator<Item = CapturedTile<'a>>) -> TiledMap {
+    let mut world = TiledMap::empty(GRID_X, GRID_Y);
+
+    for (sprite, transform, marker, animated) in tiles {
+        let Some(atlas) = sprite.texture_atlas.as_ref() else {
+            continue;
+        };
+        let index = animated.map_or(atlas.index, |animated| animated.base);
+        let Some(sprite_def) = tilemap.sprites.get(index) else {
+            continue;
+        };
+
+        // Local transform relative to the map root, centered in its cell
+        let x = (transform.translation.x / NODE_SIZE.x).floor();
+        let y = (transform.translation.y / NODE_SIZE.y).floor();
+        if x < 0.0 || y < 0.0 {
+            continue;
+        }
+
+        let (x, y) = (x as u32, y as u32);
+        let layer = layer_from_z(transform.translation.z);
+        let depth_row = depth_row_from_z(transform.translation.z, layer, GRID_Y);
+
+        world.place(
+            x,
+            y,
+            layer,
+            TiledTile {
+                sprite: sprite_def.name.clone(),
+                tile_type: marker.map(|marker| marker.tile_type),
+                row_offset: y.saturating_sub(depth_row),
+            },
+        );
+    }
+
+    world
+}
+
+fn escape_xml(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// Write a Tiled map with one inline tileset holding every atlas sprite.
+/// Tile ids are atlas sprite indices, and each tile shows its rect of the
+/// atlas image, so sprites sharing a drawing (tinted `sand` over `dirt`)
+/// still export as themselves. Tileset tiles carry the `sprite`,
+/// `tile_type` and `row_offset` properties the importer reads.
+pub fn world_to_tmx(world: &TiledMap, tilemap: &TilemapDefinition, image_source: &str) -> String {
+    // Tile type and row offset used with each sprite (first one seen wins)
+    let mut tile_types: HashMap<&str, TileType> = HashMap::new();
+    let mut row_offsets: HashMap<&str, u32> = HashMap::new();
+    for tile in world.layers.iter().flat_map(|layer| layer.tiles.iter().flatten()) {
+        if let Some(tile_type) = tile.tile_type {
+            tile_types.entry(tile.sprite.as_str()).or_insert(tile_type);
+        }
+        row_offsets.entry(tile.sprite.as_str()).or_insert(tile.row_offset);
+    }
+
+    let mut tmx = String::new();
+    let _ = writeln!(tmx, r#"<?xml version="1.0" encoding="UTF-8"?>"#);
+    let _ = writeln!(
+        tmx,
+        r#"<map version="1.10" orientation="orthogonal" renderorder="right-down" width="{}" height="{}" tilewidth="{}" tileheight="{}" infinite="0" nextlayerid="{}" nextobjectid="1">"#,
+        world.width, world.height, tilemap.tile_width, tilemap.tile_height, world.layers.len() + 1
+    );
+
+    // An image collection tileset: no image grid, one sub-rectangle per tile
+    let _ = writeln!(
+        tmx,
+        r#" <tileset firstgid="1" name="tilemap" tilewidth="{}" tileheight="{}" tilecount="{}" columns="0">"#,
+        tilemap.tile_width, tilemap.tile_height, tilemap.sprites.len()
+    );
+    let _ = writeln!(tmx, r#"  <grid orientation="orthogonal" width="1" height="1"/>"#);
+    for (id, sprite) in tilemap.sprites.iter().enumerate() {
+        let rect = tilemap.sprite_rect(id);
+        let _ = writeln!(
+            tmx,
+            r#"  <tile id="{id}" x="{}" y="{}" width="{}" height="{}">"#,
+            rect.min.x, rect.min.y, tilemap.tile_width, tilemap.tile_height
+        );
+        let _ = writeln!(tmx, "   <properties>");
+        let _ = writeln!(tmx, r#"    <property name="sprite" value="{}"/>"#, escape_xml(&sprite.name));
+        if let Some(tile_type) = tile_types.get(sprite.name.as_str()) {
+            let _ = writeln!(tmx, r#"    <property name="tile_type" value="{tile_type:?}"/>"#);
+        }
+        if let Some(row_offset) = row_offsets.get(sprite.name.as_str()).filter(|&&row_offset| row_offset > 0) {
+            let _ = writeln!(tmx, r#"    <property name="row_offset" type="int" value="{row_offset}"/>"#);
+        }
+        let _ = writeln!(tmx, "   </properties>");
+        let _ = writeln!(
+            tmx,
+            r#"   <image source="{}" width="{}" height="{}"/>"#,
+            escape_xml(image_source), tilemap.atlas_width, tilemap.atlas_height
+        );
+        let _ = writeln!(tmx, "  </tile>");
+    }
+    let _ = writeln!(tmx, " </tileset>");
+
+    for (index, layer) in world.layers.iter().enumerate() {
+        let _ = writeln!(
+            tmx,
+            r#" <layer id="{}" name="{}" width="{}" height="{}">"#,
+            index + 1, escape_xml(&layer.name), world.width, world.height
+        );
+        let _ = writeln!(tmx, "  <properties>");
+        let _ = writeln!(tmx, r#"   <property name="z" type="int" value="{}"/>"#, layer.z);
+        let _ = writeln!(tmx, "  </properties>");
+        let _ = writeln!(tmx, r#"  <data encoding="csv">"#);
+
+        // Tiled rows go top to bottom
+        let rows: Vec<String> = (0..world.height)
+            .rev()
+            .map(|y| {
+                (0..world.width)
+                    .map(|x| {
+                        let gid = layer.tiles[(y * world.width + x) as usize]
+                            .as_ref()
+                            .and_then(|tile| tilemap.sprite_index(&tile.sprite))
+                            .map_or(0, |id| id + 1);
+                        gid.to_string()
+                    })
+                    .collect::<Vec<_>>()
+                    .join(",")
+            })
+            .collect();
+        let _ = writeln!(tmx, "{}", rows.join(",\n"));
+
+        let _ = writeln!(tmx, "  </data>");
+        let _ = writeln!(tmx, " </layer>");
+    }
+
+    let _ = writeln!(tmx, "</map>");
+    tmx
+}
+
+pub fn world_to_json(world: &TiledMap, tile_size: u32) -> WorldJson {
+    let mut palette: Vec<PaletteEntry> = Vec::new();
+    let mut palette_index: HashMap<PaletteEntry, u16> = HashMap::new();
+
+    let layers = world
+        .layers
+        .iter()
+        .map(|layer| JsonLayer {
+            z: layer.z,
+            tiles: layer
+                .tiles
+                .iter()
+                .map(|tile| {
+                    let Some(tile) = tile else {
+                        return 0;
+                    };
+                    let entry = PaletteEntry {
+                        sprite: tile.sprite.clone(),
+                        tile_type: tile.tile_type.map(|tile_type| format!("{tile_type:?}")),
+                        row_offset: tile.row_offset,
+                    };
+                    *palette_index.entry(entry.clone()).or_insert_with(|| {
+                        palette.push(entry);
+                        palette.len() as u16
+                    })
+                })
+                .collect(),
+        })
+        .collect();
+
+    WorldJson {
+        width: world.width,
+        height: world.height,
+        tile_size,
+        palette,
+        layers,
+    }
+}
+
+/// Write `<name>.tmx` and `<name>.json` into `dir`.
+pub fn export_world(world: &TiledMap, tilemap: &TilemapDefinition, dir: &Path, name: &str) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+
+    // The TMX is saved under the assets folder, next to tile_layers/
+    let image_source = format!("../{ASSETS_PATH}/{TILEMAP_FILE}");
+    std::fs::write(dir.join(format!("{name}.tmx")), world_to_tmx(world, tilemap, &image_source))?;
+
+    let json = serde_json::to_string(&world_to_json(world, tilemap.tile_width))?;
+    std::fs::write(dir.join(format!("{name}.json")), json)?;
+
+    Ok(())
+}
+
+pub fn export_world_on_key(
+    input: Res<ButtonInput<KeyCode>>,
+    tilemap_res: Res<TilemapResource>,
+    tilemaps: Res<Assets<TilemapDefinition>>,
+    roots: Query<&Children, With<MapRoot>>,
+    tiles: Query<(&Sprite, &Transform, Option<&TileMarker>, Option<&AnimatedTile>)>,
+) {
+    if !input.just_pressed(KeyCode::F5) {
+        return;
+    }
+
+    let Some(tilemap) = tilemaps.get(&tilemap_res.handle) else {
+        return;
+    };
+
+    let world = capture_world(
+        tilemap,
+        roots.iter().flatten().filter_map(|child| tiles.get(*child).ok()),
+    );
+
+    let dir = Path::new(EXPORT_DIR);
+    match export_world(&world, tilemap, dir, EXPORT_NAME) {
+        Ok(()) => info!("Exported map to {}/{}.tmx and .json", EXPORT_DIR, EXPORT_NAME),
+        Err(error) => error!("Map export failed: {error}"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::ron;
+
+    use super::*;
+
+    /// Two sprites sharing one drawing (like the biome grounds), plus an animation.
+    fn tilemap() -> TilemapDefinition {
+        ron::from_str(
+            r#"(
+                tile_width: 32,
+                tile_height: 32,
+                atlas_width: 64,
+                atlas_height: 32,
+                sprites: [
+                    (name: "dirt", pixel_x: 0, pixel_y: 0),
+                    (name: "sand", pixel_x: 0, pixel_y: 0, tint: Some((1.0, 0.9, 0.6, 1.0))),
+                    (name: "water", pixel_x: 32, pixel_y: 0),
+                    (name: "water_2", pixel_x: 0, pixel_y: 0),
+                ],
+                animations: [
+                    (sprite: "water", frames: ["water", "water_2"], frame_time: 0.4),
+                ],
+            )"#,
+        )
+        .unwrap()
+    }
+
+    fn atlas_sprite(index: usize) -> Sprite {
+        Sprite::from_atlas_image(Handle::default(), TextureAtlas { layout: Handle::default(), index })
+    }
+
+    fn tile(sprite: &str) -> TiledTile {
+        TiledTile { sprite: sprite.to_string(), tile_type: None, row_offset: 0 }
+    }
+
+    #[test]
+    fn sprites_sharing_a_drawing_keep_their_own_ids() {
+        let tilemap = tilemap();
+        let mut world = TiledMap::empty(2, 1);
+        world.place(0, 0, 0, tile("dirt"));
+        world.place(1, 0, 0, tile("sand"));
+
+        let tmx = world_to_tmx(&world, &tilemap, "tilemap.png");
+
+        // gids are 1-based sprite indices
+        assert!(tmx.contains("\n1,2\n"), "{tmx}");
+        assert!(tmx.contains(r#"<tile id="1" x="0" y="0" width="32" height="32">"#));
+        assert!(tmx.contains(r#"<property name="sprite" value="sand"/>"#));
+    }
+
+    #[test]
+    fn animated_tiles_are_captured_as_their_base_sprite() {
+        let tilemap = tilemap();
+        let water = tilemap.sprite_index("water").unwrap();
+        let water_2 = tilemap.sprite_index("water_2").unwrap();
+
+        // Showing its second frame when the export happens
+        let sprite = atlas_sprite(water_2);
+        let transform = Transform::from_xyz(16.0, 16.0, 0.5);
+        let animated = AnimatedTile::new(water, vec![water, water_2], 0.4);
+
+        let world = capture_world(&tilemap, [(&sprite, &transform, None, Some(&animated))]);
+
+        assert_eq!(world.tile(0, 0, 0).map(|tile| tile.sprite.as_str()), Some("water"));
+    }
+
+    #[test]
+    fn tree_tops_keep_the_row_they_sort_with() {
+        let tilemap = tilemap();
+        let dirt = atlas_sprite(tilemap.sprite_index("dirt").unwrap());
+
+        // On layer 2, one row up, but sorted like row 0 (see depth::z_from_y)
+        let top = Transform::from_xyz(16.0, NODE_SIZE.y * 1.5, NODE_SIZE.z * 2.5 + NODE_SIZE.z);
+        let ground = Transform::from_xyz(16.0, NODE_SIZE.y * 1.5, NODE_SIZE.z * 0.5 + NODE_SIZE.z * (1.0 - 1.0 / GRID_Y as f32));
+
+        let world = capture_world(&tilemap, [(&dirt, &top, None, None), (&dirt, &ground, None, None)]);
+
+        assert_eq!(world.layers.iter().map(|layer| layer.z).collect::<Vec<_>>(), vec![0, 2]);
+        assert_eq!(world.tile(0, 0, 1).map(|tile| tile.row_offset), Some(0));
+        assert_eq!(world.tile(1, 0, 1).map(|tile| tile.row_offset), Some(1));
+    }
+}
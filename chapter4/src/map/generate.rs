@@ -1,10 +1,21 @@
 // src/map/generate.rs
+use std::hash::{BuildHasher, RandomState};
+
 use bevy_procedural_tilemaps::prelude::*;
+use bevy_procedural_tilemaps::proc_gen::GeneratorBuilderError;
 use bevy::prelude::*;
 
+use crate::collision::{TileMask, TileType, TriggerShape, TriggerZone};
+use crate::config::map::GENERATOR_RETRIES;
+use crate::state::{LoadingProgress, LoadingStage};
 use crate::map::{
-    assets::{load_assets, prepare_tilemap_handles},
+    assets::{SpawnableAsset, load_assets, prepare_tilemap_handles},
+    biomes::BiomeMap,
+    constraints::{PinnedRegion, pinned_nodes},
+    generation::PendingGeneration,
+    models::ModelNames,
     rules::build_world,
+    tilemap::TilemapDefinition,
 };
 
 // -----------------  Configurable values ---------------------------
@@ -14,28 +25,65 @@ pub const GRID_Y: u32 = 18;
 
 // ------------------------------------------------------------------
 
-const ASSETS_PATH: &str = "tile_layers";
-const TILEMAP_FILE: &str = "tilemap.png";
+pub const ASSETS_PATH: &str = "tile_layers";
+pub const TILEMAP_FILE: &str = "tilemap.png";
+/// Sprite names and positions inside TILEMAP_FILE
+const TILEMAP_ATLAS_FILE: &str = "tilemap.atlas.ron";
 /// Size of a block in world units (in Bevy 2d, 1 pixel is 1 world unit)
 pub const TILE_SIZE: f32 = 32.;
 /// Size of a grid node in world units
-const NODE_SIZE: Vec3 = Vec3::new(TILE_SIZE, TILE_SIZE, 1.);
+pub const NODE_SIZE: Vec3 = Vec3::new(TILE_SIZE, TILE_SIZE, 1.);
 
-const ASSETS_SCALE: Vec3 = Vec3::ONE;
+pub const ASSETS_SCALE: Vec3 = Vec3::ONE;
 /// Number of z layers in the map, derived from the default terrain layers.
-const GRID_Z: u32 = 5;
+pub const GRID_Z: u32 = 5;
 
 pub fn map_pixel_dimensions() -> Vec2 {
     Vec2::new(TILE_SIZE * GRID_X as f32, TILE_SIZE * GRID_Y as f32)
 }
 
-pub fn setup_generator(
-    mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
-) {
+/// The entity whose children are the map's tiles (WFC generator or Tiled import).
+#[derive(Component)]
+pub struct MapRoot;
+
+#[derive(Resource)]
+pub struct TilemapResource {
+    pub handle: Handle<TilemapDefinition>,
+}
+
+/// Start loading the atlas description; the loading screen waits for it.
+pub fn load_tilemap(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(TilemapResource {
+        handle: asset_server.load(format!("{ASSETS_PATH}/{TILEMAP_ATLAS_FILE}")),
+    });
+}
+
+/// The WFC generator of the map
+pub type MapGenerator = Generator<Cartesian3D, CartesianGrid<Cartesian3D>>;
+
+/// The world's WFC generator and what goes with it.
+pub struct WorldGenerator {
+    pub generator: MapGenerator,
+    pub grid: CartesianGrid<Cartesian3D>,
+    /// Assets to spawn for each model
+    pub assets: Vec<Vec<SpawnableAsset>>,
+    /// Model name -> index, to name models in diagnostics
+    pub model_names: ModelNames,
+}
+
+/// The grid every map is generated on.
+pub fn world_grid() -> CartesianGrid<Cartesian3D> {
+    CartesianGrid::new_cartesian_3d(GRID_X, GRID_Y, GRID_Z, false, false, false)
+}
+
+/// Build the world's WFC generator, shared by the game and headless tools.
+/// The seed drives both the biome layout and the WFC; `pins` are placed on top
+/// of the biomes before generation starts (and again on every retry).
+/// Fails when the pins contradict the rules: every try would, so the seed has
+/// to be replaced like one that ends in a contradiction.
+pub fn build_generator(seed: u64, pins: &[PinnedRegion]) -> Result<WorldGenerator, GeneratorBuilderError> {
     // 1. Rules Initialization - Get tile definitions and connection rules
-    let (assets_definitions, models, socket_collection) = build_world();
+    let (assets_definitions, models, socket_collection, model_names) = build_world();
 
     let rules = RulesBuilder::new_cartesian_3d(models, socket_collection)
         // Use ZForward as the up axis (rotation axis for models) since we are using Bevy in 2D
@@ -44,32 +92,80 @@ pub fn setup_generator(
         .unwrap();
 
     // 2. Grid - Create 3D world space with wrapping behavior (false, false, false)
-    let grid = CartesianGrid::new_cartesian_3d(GRID_X, GRID_Y, GRID_Z, false, false, false);
+    let grid = world_grid();
 
     // 3. Configuring the Algorithm - Set up WFC behavior
     let gen_builder = GeneratorBuilder::new()
         .with_rules(rules)
         .with_grid(grid.clone())
-        .with_rng(RngMode::RandomSeed)
+        .with_rng(RngMode::Seeded(seed))
         .with_node_heuristic(NodeSelectionHeuristic::MinimumRemainingValue)
-        .with_model_heuristic(ModelSelectionHeuristic::WeightedProbability);
-    
-    let generator = gen_builder.build().unwrap();
+        .with_model_heuristic(ModelSelectionHeuristic::WeightedProbability)
+        .with_max_retry_count(GENERATOR_RETRIES);
+
+    // 3b. Pinned Cells - Hand-authored regions the WFC has to build around
+    let mut regions = BiomeMap::generate(seed, GRID_X, GRID_Y).pins();
+    regions.extend_from_slice(pins);
+    let initial_nodes = pinned_nodes(&regions, &model_names, &grid);
+    let generator = gen_builder.with_initial_nodes(initial_nodes)?.build()?;
+
+    Ok(WorldGenerator {
+        generator,
+        grid,
+        assets: assets_definitions,
+        model_names,
+    })
+}
+
+/// A fresh seed for every run, without pulling in an RNG crate.
+fn random_seed() -> u64 {
+    RandomState::new().hash_one(std::time::SystemTime::now())
+}
+
+pub fn setup_generator(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    tilemap_res: Res<TilemapResource>,
+    tilemaps: Res<Assets<TilemapDefinition>>,
+    mut progress: ResMut<LoadingProgress>,
+) {
+    let Some(tilemap) = tilemaps.get(&tilemap_res.handle) else {
+        error!("Tilemap atlas isn't loaded, can't generate the map");
+        return;
+    };
+    progress.stage = LoadingStage::World;
+
+    // 1-3. Rules, grid and WFC configuration: only the assets here, the
+    // generator itself is built by the background task (see generation.rs)
+    let seed = random_seed();
+    info!("Generating map with seed {seed}");
+    let (assets, ..) = build_world();
+    let grid = world_grid();
 
     // 4. Loading Assets - Load sprite atlas and convert to renderable assets
     let tilemap_handles =
-        prepare_tilemap_handles(&asset_server, &mut atlas_layouts, ASSETS_PATH, TILEMAP_FILE);
-    let models_assets = load_assets(&tilemap_handles, assets_definitions);
+        prepare_tilemap_handles(tilemap, &asset_server, &mut atlas_layouts, ASSETS_PATH, TILEMAP_FILE);
+    let models_assets = load_assets(tilemap, &tilemap_handles, assets);
 
     // 5. Spawning the Generator - Create entity with Transform and NodesSpawner
     commands.spawn((
+        MapRoot,
         Transform::from_translation(Vec3 {
             x: -TILE_SIZE * grid.size_x() as f32 / 2.,
             y: -TILE_SIZE * grid.size_y() as f32 / 2.,
             z: 0.,
         }),
         grid,
-        generator,
+        // Runs in the background, generation::run_generation spawns the tiles
+        PendingGeneration::start(seed),
+        // Z from Y must stay on: depth::z_from_y assumes it
         NodesSpawner::new(models_assets, NODE_SIZE, ASSETS_SCALE).with_z_offset_from_y(true),
     ));
-}
\ No newline at end of file
+
+    // 6. Region Triggers - Name the areas the player can walk into
+    commands.spawn(TriggerZone::new(
+        "Lakeside",
+        TriggerShape::Tiles(TileMask::NONE.with(TileType::Shore)),
+    ));
+}
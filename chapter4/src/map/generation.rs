@@ -0,0 +1,375 @@
+// src/map/generation.rs
+//! Runs the map's WFC generator in the background and reports how it went.
+//!
+//! Generation happens on the async compute pool during `GameState::Loading`,
+//! one attempt per task, with the share of collapsed nodes shown on the
+//! loading screen. Each task builds its own generator too (rules, biomes and
+//! pins take a while), so neither the first seed nor a reseed blocks a frame. The generator gives each attempt `GENERATOR_RETRIES`
+//! restarts of its own.
+//! When an attempt still ends in a contradiction, we log what the neighbours
+//! of the failing cell still allowed and which of their sockets no model can
+//! face together, then start over with a fresh seed (new biomes, new WFC run)
+//! until `GENERATION_ATTEMPTS` runs out. A seed whose pins (biomes, spawn
+//! clearing) contradict the rules is replaced the same way.
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task, block_on, futures_lite::future};
+use bevy_procedural_tilemaps::prelude::*;
+use bevy_procedural_tilemaps::proc_gen::{GeneratorBuilderError, GeneratorError, generator::model::ModelInstance};
+use bevy_procedural_tilemaps::spawner::spawn_node;
+
+use crate::config::map::GENERATION_ATTEMPTS;
+use crate::map::{
+    analysis::{SocketConnections, opposite},
+    assets::TileBundle,
+    constraints::PINNED_REGIONS,
+    generate::{GRID_X, GRID_Y, GRID_Z, MapGenerator, WorldGenerator, build_generator},
+    models::ModelNames,
+    rules::build_rule_set,
+};
+use crate::state::{LoadingProgress, LoadingStage};
+
+/// Neighbour models listed per direction in a report, the rest are counted
+const MAX_LISTED_MODELS: usize = 6;
+/// Socket conflicts listed in a report, the rest are counted
+const MAX_LISTED_CONFLICTS: usize = 8;
+
+/// A generation attempt running in the background, on the map's root entity.
+#[derive(Component)]
+pub struct PendingGeneration {
+    pub seed: u64,
+    /// 1 for the first seed
+    pub attempt: u32,
+    total_nodes: usize,
+    /// Nodes collapsed so far by the current try, written by the task
+    collapsed: Arc<AtomicUsize>,
+    task: Task<AttemptResult>,
+}
+
+/// What a background attempt hands back: the finished generator and the
+/// tries it used, or why there is no map.
+type AttemptResult = Result<(MapGenerator, u32), AttemptFailure>;
+
+enum AttemptFailure {
+    /// The pins contradict the rules, no try was made
+    PinsRejected(GeneratorBuilderError),
+    Contradiction(ContradictionReport),
+}
+
+impl PendingGeneration {
+    /// Start the first attempt.
+    pub fn start(seed: u64) -> Self {
+        Self::start_attempt(seed, 1)
+    }
+
+    fn start_attempt(seed: u64, attempt: u32) -> Self {
+        let total_nodes = (GRID_X * GRID_Y * GRID_Z) as usize;
+        let collapsed = Arc::new(AtomicUsize::new(0));
+
+        let task_collapsed = collapsed.clone();
+        let task = AsyncComputeTaskPool::get().spawn(async move {
+            let WorldGenerator { generator, model_names, .. } =
+                build_generator(seed, PINNED_REGIONS).map_err(AttemptFailure::PinsRejected)?;
+            run_attempt(generator, &task_collapsed, &model_names)
+        });
+
+        Self { seed, attempt, total_nodes, collapsed, task }
+    }
+
+    /// Share of the map collapsed by the current try, 0..1
+    pub fn progress(&self) -> f32 {
+        self.collapsed.load(Ordering::Relaxed) as f32 / self.total_nodes as f32
+    }
+}
+
+/// One attempt, step by step so progress can be reported: like
+/// `Generator::generate_grid`, restarting up to the generator's retry count.
+/// A failure is diagnosed here too, probing the sockets takes a moment.
+fn run_attempt(mut generator: MapGenerator, collapsed: &AtomicUsize, model_names: &ModelNames) -> AttemptResult {
+    let grid = generator.grid();
+    let total_nodes = (grid.size_x() * grid.size_y() * grid.size_z()) as usize;
+    let mut last_error = None;
+
+    for try_index in 0..=generator.max_retry_count() {
+        if try_index > 0 {
+            generator.reinitialize();
+        }
+
+        loop {
+            collapsed.store(total_nodes - generator.nodes_left(), Ordering::Relaxed);
+            match generator.select_and_propagate() {
+                Ok(GenerationStatus::Ongoing) => {}
+                Ok(GenerationStatus::Done) => {
+                    return Ok((generator, try_index + 1));
+                }
+                Err(error) => {
+                    last_error = Some(error);
+                    break;
+                }
+            }
+        }
+    }
+
+    // The loop ran at least once, so it failed at least once
+    let error = last_error.expect("generation can only end on success or a contradiction");
+    Err(AttemptFailure::Contradiction(diagnose(&generator, error, model_names)))
+}
+
+/// Sent after every generation attempt.
+#[derive(Message, Debug, Clone)]
+pub enum GenerationOutcome {
+    /// The map is generated, its tiles spawn this frame
+    Succeeded { seed: u64, attempt: u32, tries: u32 },
+    /// The attempt ended in a contradiction, another seed follows if the budget allows
+    Contradiction { seed: u64, attempt: u32, report: ContradictionReport },
+    /// The seed's pins contradict the rules, so it was never tried; another
+    /// seed follows like after a contradiction
+    PinsRejected { seed: u64, attempt: u32, error: GeneratorBuilderError },
+    /// Every attempt failed, the map stays empty
+    GaveUp { attempts: u32 },
+}
+
+/// Where the WFC got stuck, and what the cells around it still allowed.
+#[derive(Debug, Clone)]
+pub struct ContradictionReport {
+    /// Grid cell (x, y, layer) left without any possible model
+    pub cell: UVec3,
+    pub neighbours: Vec<NeighbourModels>,
+    /// Sockets the neighbours show the cell that no model can face together
+    pub conflicts: Vec<SocketConflict>,
+}
+
+/// Two neighbours' sockets (direction from the failing cell, socket name)
+/// with no model that fits against both.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SocketConflict {
+    pub first: (&'static str, &'static str),
+    pub second: (&'static str, &'static str),
+}
+
+#[derive(Debug, Clone)]
+pub struct NeighbourModels {
+    pub direction: &'static str,
+    pub cell: UVec3,
+    /// Names of the models still possible there
+    pub models: Vec<String>,
+}
+
+impl fmt::Display for ContradictionReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let UVec3 { x, y, z } = self.cell;
+        write!(f, "no model fits cell ({x}, {y}) on layer {z}")?;
+
+        for neighbour in &self.neighbours {
+            let listed = &neighbour.models[..neighbour.models.len().min(MAX_LISTED_MODELS)];
+            write!(f, "\n  {:>5}: [{}]", neighbour.direction, listed.join(", "))?;
+            if neighbour.models.len() > listed.len() {
+                write!(f, " and {} more", neighbour.models.len() - listed.len())?;
+            }
+        }
+
+        for conflict in self.conflicts.iter().take(MAX_LISTED_CONFLICTS) {
+            let ((first_direction, first_socket), (second_direction, second_socket)) = (conflict.first, conflict.second);
+            write!(
+                f,
+                "\n  no model faces both {first_socket} ({first_direction}) and {second_socket} ({second_direction})"
+            )?;
+        }
+        if self.conflicts.len() > MAX_LISTED_CONFLICTS {
+            write!(f, "\n  and {} more socket conflicts", self.conflicts.len() - MAX_LISTED_CONFLICTS)?;
+        }
+        Ok(())
+    }
+}
+
+/// Describe the cells around the contradiction, as the generator left them:
+/// the models each neighbour still allowed, and the pairs of sockets they
+/// show the cell that no model can face together.
+pub fn diagnose(
+    generator: &MapGenerator,
+    error: GeneratorError,
+    model_names: &ModelNames,
+) -> ContradictionReport {
+    let grid = generator.grid();
+    let position = grid.pos_from_index(error.node_index);
+    let cell = UVec3::new(position.x, position.y, position.z);
+
+    let directions = [
+        ("left", IVec3::NEG_X, Direction::XBackward),
+        ("right", IVec3::X, Direction::XForward),
+        ("down", IVec3::NEG_Y, Direction::YBackward),
+        ("up", IVec3::Y, Direction::YForward),
+        ("below", IVec3::NEG_Z, Direction::ZBackward),
+        ("above", IVec3::Z, Direction::ZForward),
+    ];
+    let size = IVec3::new(grid.size_x() as i32, grid.size_y() as i32, grid.size_z() as i32);
+
+    // The generator was built from the same rules
+    let connections = SocketConnections::new(&build_rule_set());
+
+    let mut neighbours = Vec::new();
+    // Per neighbour: its direction, and the sockets its models show the cell
+    let mut shown: Vec<(&'static str, Direction, Vec<&'static str>)> = Vec::new();
+    for (name, delta, direction) in directions {
+        let neighbour = cell.as_ivec3() + delta;
+        if neighbour.cmplt(IVec3::ZERO).any() || neighbour.cmpge(size).any() {
+            continue;
+        }
+        let neighbour = neighbour.as_uvec3();
+        let index = grid.index_from_coords(neighbour.x, neighbour.y, neighbour.z);
+        let instances = generator.get_models_on(index);
+
+        let mut sockets: Vec<&'static str> = instances
+            .iter()
+            .filter_map(|instance| connections.variant(instance.model_index, instance.rotation))
+            .flat_map(|variant| connections.sockets_on(variant, opposite(direction)))
+            .collect();
+        sockets.sort_unstable();
+        sockets.dedup();
+        shown.push((name, direction, sockets));
+
+        let mut models: Vec<String> = instances.iter().map(|instance| model_label(model_names, instance)).collect();
+        models.dedup();
+        neighbours.push(NeighbourModels { direction: name, cell: neighbour, models });
+    }
+
+    let conflicts = socket_conflicts(&connections, &shown);
+    ContradictionReport { cell, neighbours, conflicts }
+}
+
+/// Pairs of sockets from two different neighbours that no single model can
+/// face. A neighbour carrying a socket towards the cell limits it to the models
+/// facing that socket, so two such sets that don't meet can't both be honoured.
+fn socket_conflicts(
+    connections: &SocketConnections,
+    shown: &[(&'static str, Direction, Vec<&'static str>)],
+) -> Vec<SocketConflict> {
+    let mut conflicts = Vec::new();
+    for (first, (first_name, first_direction, first_sockets)) in shown.iter().enumerate() {
+        for (second_name, second_direction, second_sockets) in &shown[first + 1..] {
+            for &first_socket in first_sockets {
+                let fits = connections.facing(first_socket, opposite(*first_direction));
+                for &second_socket in second_sockets {
+                    let others = connections.facing(second_socket, opposite(*second_direction));
+                    if !fits.iter().any(|variant| others.contains(variant)) {
+                        conflicts.push(SocketConflict {
+                            first: (first_name, first_socket),
+                            second: (second_name, second_socket),
+                        });
+                    }
+                }
+            }
+        }
+    }
+    conflicts
+}
+
+fn model_label(model_names: &ModelNames, instance: &ModelInstance) -> String {
+    let name = model_names
+        .iter()
+        .find(|(_, index)| **index == instance.model_index)
+        .map(|(name, _)| name.clone())
+        .unwrap_or_else(|| format!("model #{}", instance.model_index));
+
+    match instance.rotation {
+        ModelRotation::Rot0 => name,
+        rotation => format!("{name} ({rotation:?})"),
+    }
+}
+
+/// The seed tried after `seed` failed; deterministic so a failing run can be replayed.
+pub fn next_seed(seed: u64) -> u64 {
+    seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407)
+}
+
+/// Polls the background attempts; spawns the tiles of a finished map, or
+/// reseeds a failed one while the budget lasts.
+pub fn run_generation(
+    mut commands: Commands,
+    mut generations: Query<(Entity, &CartesianGrid<Cartesian3D>, &NodesSpawner<TileBundle>, &mut PendingGeneration)>,
+    mut progress: ResMut<LoadingProgress>,
+    mut outcomes: MessageWriter<GenerationOutcome>,
+) {
+    for (entity, grid, spawner, mut pending) in generations.iter_mut() {
+        let Some(result) = block_on(future::poll_once(&mut pending.task)) else {
+            progress.fraction = pending.progress();
+            continue;
+        };
+        let (seed, attempt) = (pending.seed, pending.attempt);
+
+        match result {
+            Ok((generator, tries)) => {
+                if let Some(nodes) = generator.to_grid_data() {
+                    for (node_index, instance) in nodes.iter().enumerate() {
+                        spawn_node(&mut commands, entity, grid, spawner, instance, node_index);
+                    }
+                }
+
+                info!("Map generated with seed {seed} (attempt {attempt}, {tries} tries)");
+                outcomes.write(GenerationOutcome::Succeeded { seed, attempt, tries });
+                progress.stage = LoadingStage::Collision;
+                // The generator stays on the map root, as it did with ProcGenSimplePlugin
+                commands.entity(entity).remove::<PendingGeneration>().insert(generator);
+            }
+            Err(failure) => {
+                match failure {
+                    AttemptFailure::PinsRejected(error) => {
+                        warn!(
+                            "Pinned regions contradict the rules with seed {seed} (attempt {attempt}/{GENERATION_ATTEMPTS}): {error}"
+                        );
+                        outcomes.write(GenerationOutcome::PinsRejected { seed, attempt, error });
+                    }
+                    AttemptFailure::Contradiction(report) => {
+                        warn!("Map generation with seed {seed} failed (attempt {attempt}/{GENERATION_ATTEMPTS}): {report}");
+                        outcomes.write(GenerationOutcome::Contradiction { seed, attempt, report });
+                    }
+                }
+
+                if attempt >= GENERATION_ATTEMPTS {
+                    error!("Giving up on map generation after {attempt} attempts");
+                    outcomes.write(GenerationOutcome::GaveUp { attempts: attempt });
+                    progress.stage = LoadingStage::Failed;
+                    commands.entity(entity).remove::<PendingGeneration>();
+                    continue;
+                }
+
+                // Reseed: new biomes and a new WFC run, same rules and assets
+                *pending = PendingGeneration::start_attempt(next_seed(seed), attempt + 1);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sand_beside_a_snow_cover_is_a_conflict() {
+        let connections = SocketConnections::new(&build_rule_set());
+        // Sand ground to the left, the snow biome's grass cover above
+        let shown = [
+            ("left", Direction::XBackward, vec!["biome.sand.material"]),
+            ("above", Direction::ZForward, vec!["biome.snow.ground_up"]),
+        ];
+
+        assert_eq!(
+            socket_conflicts(&connections, &shown),
+            vec![SocketConflict { first: ("left", "biome.sand.material"), second: ("above", "biome.snow.ground_up") }]
+        );
+    }
+
+    #[test]
+    fn snow_beside_its_own_cover_fits() {
+        let connections = SocketConnections::new(&build_rule_set());
+        let shown = [
+            ("left", Direction::XBackward, vec!["biome.snow.material"]),
+            ("above", Direction::ZForward, vec!["biome.snow.ground_up"]),
+        ];
+
+        assert!(socket_conflicts(&connections, &shown).is_empty());
+    }
+}
@@ -2,5 +2,48 @@ pub mod assets;
 pub mod tilemap;
 pub mod rules;
 pub mod models;
+pub mod constraints;
+pub mod biomes;
 pub mod sockets;
-pub mod generate;
\ No newline at end of file
+pub mod generate;
+pub mod generation;
+pub mod depth;
+pub mod occlusion;
+pub mod animation;
+pub mod tiled;
+pub mod export;
+pub mod preview;
+pub mod analysis;
+pub mod stats;
+
+use bevy::prelude::*;
+use bevy_common_assets::ron::RonAssetPlugin;
+
+use crate::state::{AssetsLoaded, GameState};
+
+pub struct MapPlugin;
+
+impl Plugin for MapPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(RonAssetPlugin::<tilemap::TilemapDefinition>::new(&["atlas.ron"]))
+            .init_asset::<tiled::TiledMap>()
+            .init_asset_loader::<tiled::TiledMapLoader>()
+            .init_resource::<occlusion::OcclusionSettings>()
+            .add_message::<generation::GenerationOutcome>()
+            .add_systems(Startup, (generate::load_tilemap, tiled::load_tiled_map))
+            // The generator needs the atlas, so it starts once the assets are in,
+            // still behind the loading screen. A configured Tiled map replaces WFC.
+            .add_systems(Update, (
+                generate::setup_generator.run_if(not(resource_exists::<tiled::TiledMapResource>)),
+                tiled::spawn_tiled_map.run_if(resource_exists::<tiled::TiledMapResource>),
+            ).run_if(resource_added::<AssetsLoaded>))
+            .add_systems(Update, generation::run_generation.run_if(in_state(GameState::Loading)))
+            .add_systems(Update, (
+                occlusion::fade_occluding_props,
+                animation::animate_tiles,
+                export::export_world_on_key,
+            ).run_if(in_state(GameState::Playing)))
+            // Depth sort after all movement, before transforms reach the renderer
+            .add_systems(PostUpdate, depth::update_y_sort_depth.before(TransformSystems::Propagate));
+    }
+}
@@ -1,11 +1,18 @@
 // src/map/models.rs
+use std::collections::HashMap;
+
 use bevy_procedural_tilemaps::prelude::*;
+use bevy_procedural_tilemaps::proc_gen::generator::model::ModelIndex;
 use crate::map::assets::SpawnableAsset;
 
+/// Model name -> model index, used to pin models by name (see `constraints`).
+pub type ModelNames = HashMap<String, ModelIndex>;
+
 /// Utility wrapper that ensures model declarations and their asset bindings stay aligned.
 pub struct TerrainModelBuilder {
     pub models: ModelCollection<Cartesian3D>,
     pub assets: Vec<Vec<SpawnableAsset>>,
+    pub names: ModelNames,
 }
 
 impl TerrainModelBuilder {
@@ -13,23 +20,55 @@ impl TerrainModelBuilder {
         Self {
             models: ModelCollection::new(),
             assets: Vec::new(),
+            names: HashMap::new(),
         }
     }
 
+    /// Models are named after their first sprite ("dirt", "water", "small_tree_bottom"...)
     pub fn create_model<T>(
         &mut self,
         template: T,
         assets: Vec<SpawnableAsset>,
     ) -> &mut Model<Cartesian3D>
+    where
+        T: Into<ModelTemplate<Cartesian3D>>,
+    {
+        let name = assets.first().map(|asset| asset.sprite_name().to_string());
+        self.create(name, template, assets)
+    }
+
+    /// For models without a sprite of their own, like each layer's void
+    pub fn create_named_model<T>(
+        &mut self,
+        name: &str,
+        template: T,
+        assets: Vec<SpawnableAsset>,
+    ) -> &mut Model<Cartesian3D>
+    where
+        T: Into<ModelTemplate<Cartesian3D>>,
+    {
+        self.create(Some(name.to_string()), template, assets)
+    }
+
+    fn create<T>(
+        &mut self,
+        name: Option<String>,
+        template: T,
+        assets: Vec<SpawnableAsset>,
+    ) -> &mut Model<Cartesian3D>
     where
         T: Into<ModelTemplate<Cartesian3D>>,
     {
         let model_ref = self.models.create(template);
+        if let Some(name) = name {
+            // The first model using a name keeps it
+            self.names.entry(name).or_insert(model_ref.index());
+        }
         self.assets.push(assets);
         model_ref
     }
 
-    pub fn into_parts(self) -> (Vec<Vec<SpawnableAsset>>, ModelCollection<Cartesian3D>) {
-        (self.assets, self.models)
+    pub fn into_parts(self) -> (Vec<Vec<SpawnableAsset>>, ModelCollection<Cartesian3D>, ModelNames) {
+        (self.assets, self.models, self.names)
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,84 @@
+// src/map/occlusion.rs
+use bevy::prelude::*;
+
+use crate::characters::config::CharacterEntry;
+use crate::characters::input::Player;
+use crate::config::occlusion::{FADED_ALPHA, FADE_SPEED};
+use crate::map::depth::YSort;
+use crate::map::generate::TILE_SIZE;
+
+/// A prop tile that fades out while it hides the player (tree tops, canopies).
+/// Added through `SpawnableAsset::with_occlusion_fade`.
+#[derive(Component, Debug, Clone, Copy)]
+pub struct Occluder {
+    /// Current opacity, eased towards its target every frame
+    pub alpha: f32,
+}
+
+impl Default for Occluder {
+    fn default() -> Self {
+        Self { alpha: 1.0 }
+    }
+}
+
+/// How far and how fast occluding props fade.
+#[derive(Resource, Debug, Clone, Copy)]
+pub struct OcclusionSettings {
+    /// Opacity of a prop while the player is behind it
+    pub faded_alpha: f32,
+    /// Opacity change per second
+    pub fade_speed: f32,
+}
+
+impl Default for OcclusionSettings {
+    fn default() -> Self {
+        Self {
+            faded_alpha: FADED_ALPHA,
+            fade_speed: FADE_SPEED,
+        }
+    }
+}
+
+/// Fade props that are drawn over the player and overlap its sprite,
+/// and bring them back once the player steps out.
+pub fn fade_occluding_props(
+    time: Res<Time>,
+    settings: Res<OcclusionSettings>,
+    player_query: Query<(&GlobalTransform, &YSort, &CharacterEntry), With<Player>>,
+    mut prop_query: Query<(&GlobalTransform, &mut Occluder, &mut Sprite)>,
+) {
+    // Player sprite bounds and depth
+    let players: Vec<(Rect, f32)> = player_query
+        .iter()
+        .map(|(transform, y_sort, character)| {
+            let center = transform.translation();
+            let half_size = character.tile_size as f32 * transform.scale().y / 2.0;
+            let bounds = Rect::from_center_half_size(center.truncate(), Vec2::splat(half_size));
+
+            // Only the part above the feet can be hidden by a prop behind them
+            let feet_y = center.y - y_sort.feet_offset;
+            let bounds = Rect::new(bounds.min.x, feet_y, bounds.max.x, bounds.max.y);
+            (bounds, center.z)
+        })
+        .collect();
+
+    let step = settings.fade_speed * time.delta_secs();
+
+    for (transform, mut occluder, mut sprite) in prop_query.iter_mut() {
+        let center = transform.translation();
+        let bounds = Rect::from_center_half_size(center.truncate(), Vec2::splat(TILE_SIZE / 2.0));
+
+        // Drawn in front of the player and covering part of it
+        let covers_player = players.iter().any(|(player_bounds, player_z)| {
+            center.z > *player_z && !bounds.intersect(*player_bounds).is_empty()
+        });
+
+        let target = if covers_player { settings.faded_alpha } else { 1.0 };
+        if occluder.alpha == target {
+            continue;
+        }
+
+        occluder.alpha += (target - occluder.alpha).clamp(-step, step);
+        sprite.color.set_alpha(occluder.alpha);
+    }
+}
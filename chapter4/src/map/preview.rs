@@ -0,0 +1,255 @@
+// src/map/preview.rs
+//! Render a whole map into an image on the CPU: no window, no GPU.
+//! Used by the `map_preview` binary to preview seeds in docs, menus and CI.
+use std::fmt;
+use std::path::Path;
+
+use bevy::asset::ron;
+use bevy::prelude::*;
+use bevy_procedural_tilemaps::prelude::*;
+use bevy_procedural_tilemaps::proc_gen::{GeneratorBuilderError, GeneratorError, generator::model::ModelInstance};
+use image::{Rgba, RgbaImage};
+
+use crate::collision::{CollisionMap, convert_water_edges_to_shore};
+use crate::map::{
+    assets::SpawnableAsset,
+    constraints::PINNED_REGIONS,
+    generate::{GRID_X, GRID_Y, TILE_SIZE, WorldGenerator, build_generator},
+    tiled::{TiledMap, TiledTile},
+    tilemap::{TilemapDefinition, TilemapError},
+};
+
+/// Overlay colours, the same as `debug_draw_collision`
+const WALKABLE_OVERLAY: [f32; 4] = [0.0, 1.0, 0.0, 0.25];
+const BLOCKED_OVERLAY: [f32; 4] = [1.0, 0.0, 0.0, 0.4];
+
+/// Lay out generated nodes the way `NodesSpawner` would spawn them.
+pub fn world_from_nodes<'a>(
+    grid: &CartesianGrid<Cartesian3D>,
+    nodes: impl IntoIterator<Item = &'a ModelInstance>,
+    assets_definitions: &[Vec<SpawnableAsset>],
+) -> TiledMap {
+    let mut world = TiledMap::empty(grid.size_x(), grid.size_y());
+
+    for (node_index, instance) in nodes.into_iter().enumerate() {
+        let Some(assets) = assets_definitions.get(instance.model_index) else {
+            continue;
+        };
+        let position = grid.pos_from_index(node_index);
+
+        for asset in assets {
+            let offset = asset.grid_offset();
+            let x = position.x as i32 + offset.dx;
+            let y = position.y as i32 + offset.dy;
+            let z = position.z as i32 + offset.dz;
+            if x < 0 || y < 0 || z < 0 {
+                continue;
+            }
+
+            world.place(
+                x as u32,
+                y as u32,
+                z as u32,
+                TiledTile {
+                    sprite: asset.sprite_name().to_string(),
+                    tile_type: asset.tile_type(),
+                    row_offset: offset.dy.max(0) as u32,
+                },
+            );
+        }
+    }
+
+    world
+}
+
+/// Why a seed gave no map.
+#[derive(Debug)]
+pub enum WorldGenerationError {
+    /// The seed's pins contradict the rules (the game reseeds these too)
+    Pins(GeneratorBuilderError),
+    /// Every try of the WFC ended in a contradiction
+    Contradiction(GeneratorError),
+}
+
+impl fmt::Display for WorldGenerationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorldGenerationError::Pins(error) => write!(f, "pinned regions contradict the rules: {error}"),
+            WorldGenerationError::Contradiction(error) => write!(f, "{error}"),
+        }
+    }
+}
+
+impl std::error::Error for WorldGenerationError {}
+
+/// Run the game's WFC with a fixed seed, without Bevy.
+pub fn generate_world(seed: u64) -> Result<TiledMap, WorldGenerationError> {
+    let WorldGenerator { mut generator, grid, assets, .. } =
+        build_generator(seed, PINNED_REGIONS).map_err(WorldGenerationError::Pins)?;
+    let (_, nodes) = generator.generate_grid().map_err(WorldGenerationError::Contradiction)?;
+    Ok(world_from_nodes(&grid, nodes.iter(), &assets))
+}
+
+/// Build the collision map the game would build for this world (shores included).
+pub fn collision_map_for(world: &TiledMap) -> CollisionMap {
+    let mut map = CollisionMap::new(
+        world.width as i32,
+        world.height as i32,
+        TILE_SIZE,
+        -TILE_SIZE * GRID_X as f32 / 2.0,
+        -TILE_SIZE * GRID_Y as f32 / 2.0,
+    );
+
+    // Layers are ordered by Z, so each cell's stack ends up bottom to top
+    for index in 0..world.layers.len() {
+        for y in 0..world.height {
+            for x in 0..world.width {
+                if let Some(tile_type) = world.tile(index, x, y).and_then(|tile| tile.tile_type) {
+                    map.push_tile(x as i32, y as i32, tile_type);
+                }
+            }
+        }
+    }
+
+    let max = IVec2::new(world.width as i32 - 1, world.height as i32 - 1);
+    convert_water_edges_to_shore(&mut map, IVec2::ZERO, max);
+    map
+}
+
+/// Alpha-blend `color` over the pixel at (x, y).
+fn blend(image: &mut RgbaImage, x: u32, y: u32, color: [f32; 4]) {
+    let pixel = image.get_pixel_mut(x, y);
+    let alpha = color[3];
+    for channel in 0..3 {
+        let dst = pixel[channel] as f32 / 255.0;
+        pixel[channel] = ((color[channel] * alpha + dst * (1.0 - alpha)) * 255.0).round() as u8;
+    }
+    let dst_alpha = pixel[3] as f32 / 255.0;
+    pixel[3] = ((alpha + dst_alpha * (1.0 - alpha)) * 255.0).round() as u8;
+}
+
+/// Composite the world from the atlas image, layer by layer, in the same
+/// order the game draws it: lower layers first, and within a layer, rows
+/// further down the screen in front (tree tops sort with their trunk's row).
+/// Sprite rects were checked against the declared atlas size, so the image
+/// has to be at least that big.
+pub fn render_world(
+    world: &TiledMap,
+    tilemap: &TilemapDefinition,
+    atlas: &RgbaImage,
+    collision: Option<&CollisionMap>,
+) -> Result<RgbaImage, TilemapError> {
+    let found = UVec2::from(atlas.dimensions());
+    if found.cmplt(tilemap.atlas_size()).any() {
+        return Err(TilemapError::ImageTooSmall { declared: tilemap.atlas_size(), found });
+    }
+
+    let (tile_width, tile_height) = (tilemap.tile_width, tilemap.tile_height);
+    let mut image = RgbaImage::from_pixel(
+        world.width * tile_width,
+        world.height * tile_height,
+        Rgba([255, 255, 255, 255]), // Same white as the game's ClearColor
+    );
+
+    let mut draws: Vec<(u32, u32, u32, &TiledTile)> = Vec::new();
+    for (index, layer) in world.layers.iter().enumerate() {
+        for y in 0..world.height {
+            for x in 0..world.width {
+                if let Some(tile) = world.tile(index, x, y) {
+                    draws.push((layer.z, y, x, tile));
+                }
+            }
+        }
+    }
+    // Y-sort: higher rows are further back
+    draws.sort_by_key(|(z, y, _, tile)| (*z, std::cmp::Reverse(tile.depth_row(*y))));
+
+    for (_, y, x, tile) in draws {
+        let Some(index) = tilemap.sprite_index(&tile.sprite) else {
+            warn!("Unknown atlas sprite '{}', skipping it", tile.sprite);
+            continue;
+        };
+        let rect = tilemap.sprite_rect(index);
+        let tint = tilemap.sprite_tint(index).to_srgba();
+
+        // Image rows go top to bottom, grid rows bottom to top
+        let out_x = x * tile_width;
+        let out_y = (world.height - 1 - y) * tile_height;
+
+        for dy in 0..tile_height {
+            for dx in 0..tile_width {
+                let src = atlas.get_pixel(rect.min.x + dx, rect.min.y + dy);
+                if src[3] == 0 {
+                    continue;
+                }
+                let color = [
+                    src[0] as f32 / 255.0 * tint.red,
+                    src[1] as f32 / 255.0 * tint.green,
+                    src[2] as f32 / 255.0 * tint.blue,
+                    src[3] as f32 / 255.0 * tint.alpha,
+                ];
+                blend(&mut image, out_x + dx, out_y + dy, color);
+            }
+        }
+    }
+
+    if let Some(map) = collision {
+        // 90% squares, like the debug gizmos
+        let inset_x = tile_width / 20;
+        let inset_y = tile_height / 20;
+
+        for y in 0..world.height {
+            for x in 0..world.width {
+                let color = if map.is_walkable(x as i32, y as i32) { WALKABLE_OVERLAY } else { BLOCKED_OVERLAY };
+                let out_x = x * tile_width;
+                let out_y = (world.height - 1 - y) * tile_height;
+
+                for dy in inset_y..tile_height - inset_y {
+                    for dx in inset_x..tile_width - inset_x {
+                        blend(&mut image, out_x + dx, out_y + dy, color);
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(image)
+}
+
+/// Read an atlas description (`.atlas.ron`) outside of the asset server.
+pub fn load_tilemap_definition(path: &Path) -> Result<TilemapDefinition, Box<dyn std::error::Error>> {
+    let text = std::fs::read_to_string(path)?;
+    Ok(ron::from_str(&text)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::ron;
+
+    use super::*;
+
+    #[test]
+    fn an_atlas_image_smaller_than_declared_is_an_error() {
+        let tilemap: TilemapDefinition = ron::from_str(
+            r#"(
+                tile_width: 32,
+                tile_height: 32,
+                atlas_width: 64,
+                atlas_height: 32,
+                sprites: [(name: "dirt", pixel_x: 32, pixel_y: 0)],
+            )"#,
+        )
+        .unwrap();
+        let mut world = TiledMap::empty(1, 1);
+        world.place(0, 0, 0, TiledTile { sprite: "dirt".to_string(), tile_type: None, row_offset: 0 });
+
+        let result = render_world(&world, &tilemap, &RgbaImage::new(32, 32), None);
+        assert_eq!(
+            result.err(),
+            Some(TilemapError::ImageTooSmall { declared: UVec2::new(64, 32), found: UVec2::new(32, 32) })
+        );
+
+        let image = render_world(&world, &tilemap, &RgbaImage::new(64, 32), None).unwrap();
+        assert_eq!(image.dimensions(), (32, 32));
+    }
+}
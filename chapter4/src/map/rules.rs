@@ -1,7 +1,8 @@
 // src/map/rules.rs
 use crate::map::assets::SpawnableAsset;
+use crate::map::biomes::Biome;
 use crate::collision::TileType;
-use crate::map::models::TerrainModelBuilder;
+use crate::map::models::{ModelNames, TerrainModelBuilder};
 use crate::map::sockets::*;
 use bevy_procedural_tilemaps::prelude::*;
 
@@ -30,13 +31,130 @@ fn build_dirt_layer(
     )]);
 }
 
+/// Ground of the desert, snowfield and swamp biomes, next to the grassland's dirt.
+/// Grassland is the hub: the other grounds only border themselves or dirt,
+/// so two different biomes are always separated by a strip of grassland.
+///
+/// Above each ground, the grass, yellow grass and water layers hold covers
+/// (empty models) that pass the biome's sockets up, so nothing grows there
+/// but the biome's own props (see `build_biome_props`), and lakes only form
+/// in wet biomes.
+fn build_biome_grounds(
+    terrain_model_builder: &mut TerrainModelBuilder,
+    terrain_sockets: &TerrainSockets,
+    socket_collection: &mut SocketCollection,
+) {
+    let void = terrain_sockets.void;
+
+    for (biome, column, tile_type) in biome_columns(terrain_sockets) {
+        let ground = biome.ground();
+        terrain_model_builder.create_model(
+            SocketsCartesian3D::Simple {
+                x_pos: column.material,
+                x_neg: column.material,
+                z_pos: column.ground_up,
+                z_neg: terrain_sockets.dirt.layer_down,
+                y_pos: column.material,
+                y_neg: column.material,
+            },
+            vec![SpawnableAsset::new(ground).with_tile_type(tile_type)],
+        );
+
+        // Covers look like the layers' voids from the side
+        let covers = [
+            ("grass_void", column.ground_up, column.grass_up),
+            ("yellow_grass_void", column.grass_up, column.yellow_grass_up),
+            ("water_void", column.yellow_grass_up, column.water_up),
+        ];
+        for (void_name, below, above) in covers {
+            terrain_model_builder.create_named_model(
+                &format!("{ground}_{void_name}"),
+                SocketsCartesian3D::Simple {
+                    x_pos: void,
+                    x_neg: void,
+                    z_pos: above,
+                    z_neg: below,
+                    y_pos: void,
+                    y_neg: void,
+                },
+                Vec::new(),
+            );
+        }
+
+        // Transitional sockets: each ground tiles with itself and with dirt
+        socket_collection.add_connections(vec![(
+            column.material,
+            vec![column.material, terrain_sockets.dirt.material],
+        )]);
+
+        // Each cover stacks on the one below; the props layer's void fits on top
+        for socket in [column.ground_up, column.grass_up, column.yellow_grass_up] {
+            socket_collection.add_rotated_connection(socket, vec![socket]);
+        }
+        socket_collection.add_rotated_connection(
+            column.water_up,
+            vec![column.water_up, terrain_sockets.props.layer_down],
+        );
+
+        if biome.has_water() {
+            socket_collection.add_rotated_connection(
+                column.yellow_grass_up,
+                vec![terrain_sockets.water.bed_down],
+            );
+        }
+    }
+}
+
+/// Each biome's props, standing on the top of its column so they only grow
+/// on that biome's ground. Named after their biome ("snow_rock_3") so they
+/// don't take the grassland props' names.
+fn build_biome_props(terrain_model_builder: &mut TerrainModelBuilder, terrain_sockets: &TerrainSockets) {
+    for (biome, column, _) in biome_columns(terrain_sockets) {
+        for prop in biome.props() {
+            let mut assets = vec![SpawnableAsset::new(prop.sprite).with_tile_type(prop.tile_type)];
+            if let Some(top) = prop.top {
+                assets.push(
+                    SpawnableAsset::new(top)
+                        .with_grid_offset(GridDelta::new(0, 1, 0))
+                        .with_occlusion_fade(),
+                );
+            }
+
+            terrain_model_builder
+                .create_named_model(
+                    &format!("{}_{}", biome.ground(), prop.sprite),
+                    SocketsCartesian3D::Simple {
+                        x_pos: terrain_sockets.void,
+                        x_neg: terrain_sockets.void,
+                        z_pos: terrain_sockets.props.layer_up,
+                        z_neg: column.water_up,
+                        y_pos: terrain_sockets.void,
+                        y_neg: terrain_sockets.void,
+                    },
+                    assets,
+                )
+                .with_weight(prop.weight);
+        }
+    }
+}
+
+/// The non-grassland biomes with their sockets and ground tile type
+fn biome_columns(terrain_sockets: &TerrainSockets) -> [(Biome, &BiomeColumnSockets, TileType); 3] {
+    [
+        (Biome::Desert, &terrain_sockets.biome.sand, TileType::Sand),
+        (Biome::Snowfield, &terrain_sockets.biome.snow, TileType::Snow),
+        (Biome::Swamp, &terrain_sockets.biome.mud, TileType::Mud),
+    ]
+}
+
 fn build_yellow_grass_layer(
     terrain_model_builder: &mut TerrainModelBuilder,
     terrain_sockets: &TerrainSockets,
     socket_collection: &mut SocketCollection,
 ) {
     // Void model - empty space where no yellow grass exists
-    terrain_model_builder.create_model(
+    terrain_model_builder.create_named_model(
+        "yellow_grass_void",
         SocketsCartesian3D::Simple {
             x_pos: terrain_sockets.void,
             x_neg: terrain_sockets.void,
@@ -169,14 +287,15 @@ fn build_grass_layer(
     socket_collection: &mut SocketCollection,
 ) {
     // Void model - empty space above dirt where no grass exists
-    terrain_model_builder.create_model(
-        SocketsCartesian3D::Simple {
-            x_pos: terrain_sockets.void,
-            x_neg: terrain_sockets.void,
-            z_pos: terrain_sockets.grass.layer_up,
-            z_neg: terrain_sockets.grass.layer_down,
-            y_pos: terrain_sockets.void,
-            y_neg: terrain_sockets.void,
+    terrain_model_builder.create_named_model(
+        "grass_void",
+        SocketsCartesian3D::Multiple {
+            x_pos: vec![terrain_sockets.void],
+            x_neg: vec![terrain_sockets.void],
+            z_pos: vec![terrain_sockets.grass.layer_up],
+            z_neg: vec![terrain_sockets.grass.layer_down],
+            y_pos: vec![terrain_sockets.void],
+            y_neg: vec![terrain_sockets.void],
         },
         Vec::new(),
     );
@@ -310,7 +429,8 @@ pub fn build_water_layer(
     socket_collection: &mut SocketCollection,
 ) {
     // Void model - represents land areas where no water exists
-    terrain_model_builder.create_model(
+    terrain_model_builder.create_named_model(
+        "water_void",
         SocketsCartesian3D::Multiple {
             x_pos: vec![terrain_sockets.void],
             x_neg: vec![terrain_sockets.void],
@@ -333,7 +453,7 @@ pub fn build_water_layer(
                 x_pos: terrain_sockets.water.material,
                 x_neg: terrain_sockets.water.material,
                 z_pos: terrain_sockets.water.layer_up,
-                z_neg: terrain_sockets.water.layer_down,
+                z_neg: terrain_sockets.water.bed_down,
                 y_pos: terrain_sockets.water.material,
                 y_neg: terrain_sockets.water.material,
             },
@@ -346,7 +466,7 @@ pub fn build_water_layer(
         x_pos: terrain_sockets.water.void_and_water,
         x_neg: terrain_sockets.void,
         z_pos: terrain_sockets.water.layer_up,
-        z_neg: terrain_sockets.water.layer_down,
+        z_neg: terrain_sockets.water.bed_down,
         y_pos: terrain_sockets.void,
         y_neg: terrain_sockets.water.water_and_void,
     }
@@ -358,7 +478,7 @@ pub fn build_water_layer(
         x_pos: terrain_sockets.water.water_and_void,
         x_neg: terrain_sockets.water.material,
         z_pos: terrain_sockets.water.layer_up,
-        z_neg: terrain_sockets.water.layer_down,
+        z_neg: terrain_sockets.water.bed_down,
         y_pos: terrain_sockets.water.material,
         y_neg: terrain_sockets.water.void_and_water,
     }
@@ -370,7 +490,7 @@ pub fn build_water_layer(
         x_pos: terrain_sockets.water.void_and_water,
         x_neg: terrain_sockets.water.water_and_void,
         z_pos: terrain_sockets.water.layer_up,
-        z_neg: terrain_sockets.water.layer_down,
+        z_neg: terrain_sockets.water.bed_down,
         y_pos: terrain_sockets.void,
         y_neg: terrain_sockets.water.material,
     }
@@ -443,10 +563,10 @@ pub fn build_water_layer(
         ),
     ]);
 
-    // Connect water layer to yellow grass layer
+    // Connect water layer to yellow grass layer: both land and lakes fit on it
     socket_collection.add_rotated_connection(
         terrain_sockets.yellow_grass.layer_up,
-        vec![terrain_sockets.water.layer_down],
+        vec![terrain_sockets.water.layer_down, terrain_sockets.water.bed_down],
     );
 }
 
@@ -457,7 +577,8 @@ pub fn build_props_layer(
     socket_collection: &mut SocketCollection,
 ) {
     // Void model - represents areas where no props exist
-    terrain_model_builder.create_model(
+    terrain_model_builder.create_named_model(
+        "props_void",
         SocketsCartesian3D::Multiple {
             x_pos: vec![terrain_sockets.void],
             x_neg: vec![terrain_sockets.void],
@@ -497,7 +618,9 @@ pub fn build_props_layer(
         plant_prop.clone(),
         vec![
             SpawnableAsset::new("small_tree_bottom").with_tile_type(TileType::Tree),
-            SpawnableAsset::new("small_tree_top").with_grid_offset(GridDelta::new(0, 1, 0)),
+            SpawnableAsset::new("small_tree_top")
+                .with_grid_offset(GridDelta::new(0, 1, 0))
+                .with_occlusion_fade(),
         ],
     );
 
@@ -513,8 +636,12 @@ pub fn build_props_layer(
                 y_neg: terrain_sockets.void,
             },
             vec![
-                SpawnableAsset::new("big_tree_1_bl").with_tile_type(TileType::Tree),
-                SpawnableAsset::new("big_tree_1_tl").with_grid_offset(GridDelta::new(0, 1, 0)),
+                SpawnableAsset::new("big_tree_1_bl")
+                    .with_tile_type(TileType::Tree)
+                    .with_occlusion_fade(),
+                SpawnableAsset::new("big_tree_1_tl")
+                    .with_grid_offset(GridDelta::new(0, 1, 0))
+                    .with_occlusion_fade(),
             ],
         )
         .with_weight(PROPS_WEIGHT);
@@ -530,8 +657,12 @@ pub fn build_props_layer(
                 y_neg: terrain_sockets.void,
             },
             vec![
-                SpawnableAsset::new("big_tree_1_br").with_tile_type(TileType::Tree),
-                SpawnableAsset::new("big_tree_1_tr").with_grid_offset(GridDelta::new(0, 1, 0)),
+                SpawnableAsset::new("big_tree_1_br")
+                    .with_tile_type(TileType::Tree)
+                    .with_occlusion_fade(),
+                SpawnableAsset::new("big_tree_1_tr")
+                    .with_grid_offset(GridDelta::new(0, 1, 0))
+                    .with_occlusion_fade(),
             ],
         )
         .with_weight(PROPS_WEIGHT);
@@ -548,8 +679,12 @@ pub fn build_props_layer(
                 y_neg: terrain_sockets.void,
             },
             vec![
-                SpawnableAsset::new("big_tree_2_bl").with_tile_type(TileType::Tree),
-                SpawnableAsset::new("big_tree_2_tl").with_grid_offset(GridDelta::new(0, 1, 0)),
+                SpawnableAsset::new("big_tree_2_bl")
+                    .with_tile_type(TileType::Tree)
+                    .with_occlusion_fade(),
+                SpawnableAsset::new("big_tree_2_tl")
+                    .with_grid_offset(GridDelta::new(0, 1, 0))
+                    .with_occlusion_fade(),
             ],
         )
         .with_weight(PROPS_WEIGHT);
@@ -565,8 +700,12 @@ pub fn build_props_layer(
                 y_neg: terrain_sockets.void,
             },
             vec![
-                SpawnableAsset::new("big_tree_2_br").with_tile_type(TileType::Tree),
-                SpawnableAsset::new("big_tree_2_tr").with_grid_offset(GridDelta::new(0, 1, 0)),
+                SpawnableAsset::new("big_tree_2_br")
+                    .with_tile_type(TileType::Tree)
+                    .with_occlusion_fade(),
+                SpawnableAsset::new("big_tree_2_tr")
+                    .with_grid_offset(GridDelta::new(0, 1, 0))
+                    .with_occlusion_fade(),
             ],
         )
         .with_weight(PROPS_WEIGHT);
@@ -574,15 +713,15 @@ pub fn build_props_layer(
     // Tree stumps
     terrain_model_builder.create_model(
         stump_prop.clone(),
-        vec![SpawnableAsset::new("tree_stump_1").with_tile_type(TileType::Tree)],
+        vec![SpawnableAsset::new("tree_stump_1").with_tile_type(TileType::Stump)],
     );
     terrain_model_builder.create_model(
         stump_prop.clone(),
-        vec![SpawnableAsset::new("tree_stump_2").with_tile_type(TileType::Tree)],
+        vec![SpawnableAsset::new("tree_stump_2").with_tile_type(TileType::Stump)],
     );
     terrain_model_builder.create_model(
         stump_prop.clone(),
-        vec![SpawnableAsset::new("tree_stump_3").with_tile_type(TileType::Tree)],
+        vec![SpawnableAsset::new("tree_stump_3").with_tile_type(TileType::Stump)],
     );
 
     // Rocks
@@ -621,11 +760,27 @@ pub fn build_props_layer(
         );
 }
 
+/// Everything `build_world` declares, with the named sockets for tools like
+/// the rule-set analyzer (`map::analysis`).
+pub struct RuleSet {
+    pub assets: Vec<Vec<SpawnableAsset>>,
+    pub models: ModelCollection<Cartesian3D>,
+    pub sockets: SocketCollection,
+    pub terrain_sockets: TerrainSockets,
+    pub model_names: ModelNames,
+}
+
 pub fn build_world() -> (
     Vec<Vec<SpawnableAsset>>,
     ModelCollection<Cartesian3D>,
     SocketCollection,
+    ModelNames,
 ) {
+    let RuleSet { assets, models, sockets, model_names, .. } = build_rule_set();
+    (assets, models, sockets, model_names)
+}
+
+pub fn build_rule_set() -> RuleSet {
     let mut socket_collection = SocketCollection::new();
     let terrain_sockets = create_sockets(&mut socket_collection);
 
@@ -638,6 +793,13 @@ pub fn build_world() -> (
         &mut socket_collection,
     );
 
+    // Build the other biomes' grounds, on the dirt layer
+    build_biome_grounds(
+        &mut terrain_model_builder,
+        &terrain_sockets,
+        &mut socket_collection,
+    );
+
     build_grass_layer(
         &mut terrain_model_builder,
         &terrain_sockets,
@@ -665,7 +827,16 @@ pub fn build_world() -> (
         &mut socket_collection,
     );
 
-    let (assets, models) = terrain_model_builder.into_parts();
+    // Props of the other biomes, on top of their grounds
+    build_biome_props(&mut terrain_model_builder, &terrain_sockets);
 
-    (assets, models, socket_collection)
+    let (assets, models, model_names) = terrain_model_builder.into_parts();
+
+    RuleSet {
+        assets,
+        models,
+        sockets: socket_collection,
+        terrain_sockets,
+        model_names,
+    }
 }
\ No newline at end of file
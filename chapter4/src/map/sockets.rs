@@ -7,6 +7,7 @@ pub struct TerrainSockets {
     pub yellow_grass: YellowGrassLayerSockets,
     pub water: WaterLayerSockets,
     pub props: PropsLayerSockets, // Add this line
+    pub biome: BiomeSockets,
 }
 
 pub struct DirtLayerSockets {
@@ -15,6 +16,24 @@ pub struct DirtLayerSockets {
     pub material: Socket,       // What dirt connects to horizontally
 }
 
+/// Ground of the non-grassland biomes, on the dirt layer
+pub struct BiomeSockets {
+    pub sand: BiomeColumnSockets,   // Desert
+    pub snow: BiomeColumnSockets,   // Snowfield
+    pub mud: BiomeColumnSockets,    // Swamp
+}
+
+/// Carry one biome up through the layers it keeps bare, so the props
+/// layer knows which biome it stands on. Each socket is the top of one
+/// layer's cover and connects to itself.
+pub struct BiomeColumnSockets {
+    pub material: Socket,         // What the ground connects to horizontally
+    pub ground_up: Socket,        // Top of the ground, on the dirt layer
+    pub grass_up: Socket,         // Top of the cover on the grass layer
+    pub yellow_grass_up: Socket,  // Top of the cover on the yellow grass layer
+    pub water_up: Socket,         // Top of the cover on the water layer, the biome's props stand on it
+}
+
 pub struct GrassLayerSockets {
     pub layer_up: Socket,
     pub layer_down: Socket,
@@ -37,6 +56,7 @@ pub struct WaterLayerSockets {
     pub void_and_water: Socket,
     pub water_and_void: Socket,
     pub ground_up: Socket,
+    pub bed_down: Socket,       // What water tiles sit on, offered by wet biomes too
 }
 
 pub struct PropsLayerSockets {
@@ -48,6 +68,65 @@ pub struct PropsLayerSockets {
 }
 
 
+impl BiomeColumnSockets {
+    fn new(new_socket: &mut impl FnMut() -> Socket) -> Self {
+        Self {
+            material: new_socket(),
+            ground_up: new_socket(),
+            grass_up: new_socket(),
+            yellow_grass_up: new_socket(),
+            water_up: new_socket(),
+        }
+    }
+}
+
+impl TerrainSockets {
+    /// Every socket with its name ("grass.material"), in declaration order
+    pub fn named(&self) -> Vec<(&'static str, Socket)> {
+        vec![
+            ("dirt.layer_up", self.dirt.layer_up),
+            ("dirt.layer_down", self.dirt.layer_down),
+            ("dirt.material", self.dirt.material),
+            ("void", self.void),
+            ("grass.layer_up", self.grass.layer_up),
+            ("grass.layer_down", self.grass.layer_down),
+            ("grass.material", self.grass.material),
+            ("grass.void_and_grass", self.grass.void_and_grass),
+            ("grass.grass_and_void", self.grass.grass_and_void),
+            ("grass.grass_fill_up", self.grass.grass_fill_up),
+            ("yellow_grass.layer_up", self.yellow_grass.layer_up),
+            ("yellow_grass.layer_down", self.yellow_grass.layer_down),
+            ("yellow_grass.yellow_grass_fill_down", self.yellow_grass.yellow_grass_fill_down),
+            ("water.layer_up", self.water.layer_up),
+            ("water.layer_down", self.water.layer_down),
+            ("water.material", self.water.material),
+            ("water.void_and_water", self.water.void_and_water),
+            ("water.water_and_void", self.water.water_and_void),
+            ("water.ground_up", self.water.ground_up),
+            ("water.bed_down", self.water.bed_down),
+            ("props.layer_up", self.props.layer_up),
+            ("props.layer_down", self.props.layer_down),
+            ("props.props_down", self.props.props_down),
+            ("props.big_tree_1_base", self.props.big_tree_1_base),
+            ("props.big_tree_2_base", self.props.big_tree_2_base),
+            ("biome.sand.material", self.biome.sand.material),
+            ("biome.sand.ground_up", self.biome.sand.ground_up),
+            ("biome.sand.grass_up", self.biome.sand.grass_up),
+            ("biome.sand.yellow_grass_up", self.biome.sand.yellow_grass_up),
+            ("biome.sand.water_up", self.biome.sand.water_up),
+            ("biome.snow.material", self.biome.snow.material),
+            ("biome.snow.ground_up", self.biome.snow.ground_up),
+            ("biome.snow.grass_up", self.biome.snow.grass_up),
+            ("biome.snow.yellow_grass_up", self.biome.snow.yellow_grass_up),
+            ("biome.snow.water_up", self.biome.snow.water_up),
+            ("biome.mud.material", self.biome.mud.material),
+            ("biome.mud.ground_up", self.biome.mud.ground_up),
+            ("biome.mud.grass_up", self.biome.mud.grass_up),
+            ("biome.mud.yellow_grass_up", self.biome.mud.yellow_grass_up),
+            ("biome.mud.water_up", self.biome.mud.water_up),
+        ]
+    }
+}
 
 pub fn create_sockets(socket_collection: &mut SocketCollection) -> TerrainSockets {
     let mut new_socket = || -> Socket { socket_collection.create() };
@@ -79,6 +158,7 @@ pub fn create_sockets(socket_collection: &mut SocketCollection) -> TerrainSocket
             void_and_water: new_socket(),
             water_and_void: new_socket(),
             ground_up: new_socket(),
+            bed_down: new_socket(),
         },
         props: PropsLayerSockets {
             layer_up: new_socket(),
@@ -87,6 +167,11 @@ pub fn create_sockets(socket_collection: &mut SocketCollection) -> TerrainSocket
             big_tree_1_base: new_socket(),
             big_tree_2_base: new_socket(),
         },
+        biome: BiomeSockets {
+            sand: BiomeColumnSockets::new(&mut new_socket),
+            snow: BiomeColumnSockets::new(&mut new_socket),
+            mud: BiomeColumnSockets::new(&mut new_socket),
+        },
     };
     sockets
 }
\ No newline at end of file
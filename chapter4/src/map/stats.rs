@@ -0,0 +1,244 @@
+// src/map/stats.rs
+//! Measure generated maps, to compare rule revisions (weights, sockets,
+//! biomes) on numbers instead of screenshots. Used by the `map_stats` binary.
+//!
+//! Each seed runs like `map_preview` does: one generator with its own retries,
+//! no reseeding, so a failure here is a seed the game would have to replace.
+use std::collections::{BTreeMap, VecDeque};
+use std::fmt::Write as _;
+use std::time::Instant;
+
+use serde::Serialize;
+
+use crate::collision::{CollisionMap, TileMask, TileType};
+use crate::map::preview::{collision_map_for, generate_world};
+
+/// Water and its shore: one body per connected area of them
+const WATER: TileMask = TileMask::NONE.with(TileType::Water).with(TileType::Shore);
+
+/// One seed's generation.
+#[derive(Debug, Clone, Serialize)]
+pub struct MapStats {
+    pub seed: u64,
+    /// Rules, biomes and WFC, the way the game builds a map
+    pub generation_ms: f64,
+    /// `None` when generation failed
+    pub layout: Option<MapLayout>,
+}
+
+/// What a generated map looks like to the player.
+#[derive(Debug, Clone, Serialize)]
+pub struct MapLayout {
+    /// Cells per topmost tile type (a tree on grass counts as a tree)
+    pub tiles: BTreeMap<String, usize>,
+    /// Share of the cells the player can walk on, 0 to 100
+    pub walkable_percent: f32,
+    pub water_bodies: usize,
+    /// Cells in the largest walkable area, moving in 4 directions
+    pub largest_walkable_region: usize,
+}
+
+/// Averages over a batch, failed maps left out of the layout means.
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchSummary {
+    pub label: String,
+    pub maps: usize,
+    pub failures: usize,
+    pub failure_rate: f32,
+    pub mean_generation_ms: f64,
+    pub mean_walkable_percent: f32,
+    pub mean_water_bodies: f32,
+    pub mean_largest_walkable_region: f32,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BatchReport {
+    pub summary: BatchSummary,
+    pub maps: Vec<MapStats>,
+}
+
+/// Generate the map of `seed` and measure it.
+pub fn measure_seed(seed: u64) -> MapStats {
+    let start = Instant::now();
+    let world = generate_world(seed);
+    let generation_ms = start.elapsed().as_secs_f64() * 1000.0;
+
+    MapStats {
+        seed,
+        generation_ms,
+        layout: world.ok().map(|world| measure_layout(&collision_map_for(&world))),
+    }
+}
+
+/// Measure `count` maps from `first_seed` on, one seed after the other
+/// (wrapping past `u64::MAX`).
+pub fn measure_batch(label: &str, first_seed: u64, count: usize) -> BatchReport {
+    let maps: Vec<MapStats> =
+        (0..count as u64).map(|offset| measure_seed(first_seed.wrapping_add(offset))).collect();
+    BatchReport { summary: summarize(label, &maps), maps }
+}
+
+pub fn measure_layout(map: &CollisionMap) -> MapLayout {
+    let mut tiles: BTreeMap<String, usize> =
+        TileType::ALL.iter().map(|tile_type| (format!("{tile_type:?}"), 0)).collect();
+    let mut walkable = 0;
+
+    for y in 0..map.height() {
+        for x in 0..map.width() {
+            if let Some(tile_type) = map.get_tile(x, y) {
+                *tiles.entry(format!("{tile_type:?}")).or_default() += 1;
+            }
+            if map.is_walkable(x, y) {
+                walkable += 1;
+            }
+        }
+    }
+
+    let cells = (map.width() * map.height()).max(1);
+    MapLayout {
+        tiles,
+        walkable_percent: walkable as f32 * 100.0 / cells as f32,
+        water_bodies: regions(map, |x, y| map.top_in_mask(x, y, WATER).is_some()).len(),
+        largest_walkable_region: regions(map, |x, y| map.is_walkable(x, y)).into_iter().max().unwrap_or(0),
+    }
+}
+
+/// Sizes of the 4-connected areas of cells where `inside` holds.
+fn regions(map: &CollisionMap, inside: impl Fn(i32, i32) -> bool) -> Vec<usize> {
+    let (width, height) = (map.width(), map.height());
+    let mut visited = vec![false; (width * height) as usize];
+    let mut sizes = Vec::new();
+
+    for start_y in 0..height {
+        for start_x in 0..width {
+            let start = (start_y * width + start_x) as usize;
+            if visited[start] || !inside(start_x, start_y) {
+                continue;
+            }
+
+            visited[start] = true;
+            let mut queue = VecDeque::from([(start_x, start_y)]);
+            let mut size = 0;
+            while let Some((x, y)) = queue.pop_front() {
+                size += 1;
+                for (nx, ny) in [(x + 1, y), (x - 1, y), (x, y + 1), (x, y - 1)] {
+                    if !map.in_bounds(nx, ny) {
+                        continue;
+                    }
+                    let index = (ny * width + nx) as usize;
+                    if !visited[index] && inside(nx, ny) {
+                        visited[index] = true;
+                        queue.push_back((nx, ny));
+                    }
+                }
+            }
+            sizes.push(size);
+        }
+    }
+
+    sizes
+}
+
+pub fn summarize(label: &str, maps: &[MapStats]) -> BatchSummary {
+    let layouts: Vec<&MapLayout> = maps.iter().filter_map(|map| map.layout.as_ref()).collect();
+    let failures = maps.len() - layouts.len();
+    let mean = |values: Vec<f32>| if values.is_empty() { 0.0 } else { values.iter().sum::<f32>() / values.len() as f32 };
+
+    BatchSummary {
+        label: label.to_string(),
+        maps: maps.len(),
+        failures,
+        failure_rate: if maps.is_empty() { 0.0 } else { failures as f32 / maps.len() as f32 },
+        mean_generation_ms: maps.iter().map(|map| map.generation_ms).sum::<f64>() / maps.len().max(1) as f64,
+        mean_walkable_percent: mean(layouts.iter().map(|layout| layout.walkable_percent).collect()),
+        mean_water_bodies: mean(layouts.iter().map(|layout| layout.water_bodies as f32).collect()),
+        mean_largest_walkable_region: mean(layouts.iter().map(|layout| layout.largest_walkable_region as f32).collect()),
+    }
+}
+
+/// One row per map, one column per tile type. Failed maps leave the layout
+/// columns empty. The label column lets batches of several rule revisions
+/// share one spreadsheet.
+pub fn batch_to_csv(report: &BatchReport) -> String {
+    let mut csv = String::from("label,seed,generated,generation_ms,walkable_percent,water_bodies,largest_walkable_region");
+    for tile_type in TileType::ALL {
+        let _ = write!(csv, ",{tile_type:?}");
+    }
+    csv.push('\n');
+
+    for map in &report.maps {
+        let _ = write!(
+            csv,
+            "{},{},{},{:.3}",
+            report.summary.label,
+            map.seed,
+            map.layout.is_some(),
+            map.generation_ms
+        );
+        match &map.layout {
+            Some(layout) => {
+                let _ = write!(
+                    csv,
+                    ",{:.2},{},{}",
+                    layout.walkable_percent, layout.water_bodies, layout.largest_walkable_region
+                );
+                for tile_type in TileType::ALL {
+                    let cells = layout.tiles.get(&format!("{tile_type:?}")).copied().unwrap_or(0);
+                    let _ = write!(csv, ",{cells}");
+                }
+            }
+            None => csv.push_str(&",".repeat(3 + TileType::ALL.len())),
+        }
+        csv.push('\n');
+    }
+
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 5x4 grass map with `tiles` on top.
+    fn map_with(tiles: &[(i32, i32, TileType)]) -> CollisionMap {
+        let mut map = CollisionMap::new(5, 4, 10.0, 0.0, 0.0);
+        for y in 0..4 {
+            for x in 0..5 {
+                map.push_tile(x, y, TileType::Grass);
+            }
+        }
+        for &(x, y, tile_type) in tiles {
+            map.push_tile(x, y, tile_type);
+        }
+        map
+    }
+
+    #[test]
+    fn layout_counts_water_bodies_and_the_largest_walkable_region() {
+        // A line of trees at x = 3 cuts the map in two, a pond on each side
+        let mut tiles = vec![(1, 1, TileType::Water), (4, 3, TileType::Water)];
+        tiles.extend((0..4).map(|y| (3, y, TileType::Tree)));
+        let layout = measure_layout(&map_with(&tiles));
+
+        assert_eq!(layout.water_bodies, 2);
+        // 3 columns of 4 cells on the left, less the pond
+        assert_eq!(layout.largest_walkable_region, 11);
+        assert_eq!(layout.tiles["Tree"], 4);
+        assert_eq!(layout.walkable_percent, 14.0 * 100.0 / 20.0);
+    }
+
+    #[test]
+    fn failed_maps_keep_every_csv_column() {
+        let generated = MapStats { seed: 1, generation_ms: 2.0, layout: Some(measure_layout(&map_with(&[]))) };
+        let failed = MapStats { seed: 2, generation_ms: 3.0, layout: None };
+        let maps = vec![generated, failed];
+        let report = BatchReport { summary: summarize("test", &maps), maps };
+
+        let csv = batch_to_csv(&report);
+        let columns: Vec<usize> = csv.lines().map(|line| line.split(',').count()).collect();
+
+        assert_eq!(columns.len(), 3);
+        assert!(columns.iter().all(|&count| count == columns[0]), "{csv}");
+        assert!(csv.lines().nth(2).unwrap().starts_with("test,2,false,3.000,"));
+    }
+}
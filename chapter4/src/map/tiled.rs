@@ -0,0 +1,525 @@
+// src/map/tiled.rs
+//! Import hand-made levels from Tiled (.tmx with inline or external .tsx tilesets).
+//!
+//! Each tileset tile needs custom properties telling us what to spawn:
+//! - `sprite` (string): sprite name in the tilemap atlas, e.g. "green_grass"
+//! - `tile_type` (string, optional): `TileType` variant for collision, e.g. "Grass"
+//! - `row_offset` (int, optional): rows above the cell the tile depth-sorts
+//!   with, e.g. 1 for a tree top so it sorts with its trunk like in WFC maps
+//!
+//! Tile layers become Z layers, bottom to top, like the WFC grid's layers.
+//! A layer can pick its Z layer explicitly with an int `z` property.
+//! Layer data must use Tiled's default CSV encoding.
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+use bevy::asset::{AssetLoader, LoadContext, ReadAssetBytesError, io::Reader};
+use bevy::prelude::*;
+
+use bevy_procedural_tilemaps::prelude::*;
+
+use crate::collision::TileType;
+use crate::map::{
+    assets::{TileHook, prepare_tilemap_handles, tile_bundle},
+    generate::{ASSETS_PATH, ASSETS_SCALE, GRID_X, GRID_Y, MapRoot, NODE_SIZE, TILE_SIZE, TILEMAP_FILE, TilemapResource},
+    rules::build_rule_set,
+    tilemap::TilemapDefinition,
+};
+
+/// Tiled stores flip/rotation flags in the top bits of each gid.
+const GID_FLAGS_MASK: u32 = 0x1FFF_FFFF;
+
+/// What a single Tiled cell spawns.
+#[derive(Debug, Clone)]
+pub struct TiledTile {
+    pub sprite: String,
+    pub tile_type: Option<TileType>,
+    /// Rows above the grid node the tile was spawned for (an asset's
+    /// `grid_offset`); it depth-sorts with that node's row
+    pub row_offset: u32,
+}
+
+impl TiledTile {
+    /// Row the tile depth-sorts with
+    pub fn depth_row(&self, y: u32) -> u32 {
+        y.saturating_sub(self.row_offset)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct TiledLayer {
+    pub name: String,
+    /// Z layer in the grid (several Tiled layers may share one)
+    pub z: u32,
+    /// Row-major, row 0 at the bottom (flipped from Tiled's top-down rows)
+    pub tiles: Vec<Option<TiledTile>>,
+}
+
+/// A Tiled map, resolved to atlas sprite names and tile types.
+#[derive(Asset, TypePath, Debug, Clone)]
+pub struct TiledMap {
+    pub width: u32,
+    pub height: u32,
+    pub layers: Vec<TiledLayer>,
+}
+
+impl TiledMap {
+    pub fn empty(width: u32, height: u32) -> Self {
+        Self { width, height, layers: Vec::new() }
+    }
+
+    /// Put a tile on Z layer `z`. If that cell is already taken on every layer
+    /// with this `z` (e.g. a tree top over a plant), an extra layer is added.
+    pub fn place(&mut self, x: u32, y: u32, z: u32, tile: TiledTile) {
+        if x >= self.width || y >= self.height {
+            return;
+        }
+        let cell = (y * self.width + x) as usize;
+
+        let free = self
+            .layers
+            .iter_mut()
+            .find(|layer| layer.z == z && layer.tiles[cell].is_none());
+        if let Some(layer) = free {
+            layer.tiles[cell] = Some(tile);
+            return;
+        }
+
+        let overlap = self.layers.iter().filter(|layer| layer.z == z).count();
+        let name = if overlap == 0 {
+            format!("layer_{z}")
+        } else {
+            format!("layer_{z}_overlap_{overlap}")
+        };
+        let mut tiles = vec![None; (self.width * self.height) as usize];
+        tiles[cell] = Some(tile);
+
+        // Keep layers ordered by Z
+        let index = self.layers.iter().position(|layer| layer.z > z).unwrap_or(self.layers.len());
+        self.layers.insert(index, TiledLayer { name, z, tiles });
+    }
+
+    pub fn tile(&self, layer: usize, x: u32, y: u32) -> Option<&TiledTile> {
+        self.layers.get(layer)?.tiles.get((y * self.width + x) as usize)?.as_ref()
+    }
+}
+
+#[derive(Debug)]
+pub enum TiledError {
+    Io(std::io::Error),
+    Xml(roxmltree::Error),
+    Tileset { source: String, error: ReadAssetBytesError },
+    MissingAttribute { element: &'static str, attribute: &'static str },
+    UnsupportedEncoding(String),
+    LayerSize { layer: String, expected: usize, found: usize },
+    InvalidGid(String),
+    UnknownGid(u32),
+    UnknownTileType(String),
+    InvalidRowOffset(String),
+}
+
+impl fmt::Display for TiledError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TiledError::Io(error) => write!(f, "couldn't read map: {error}"),
+            TiledError::Xml(error) => write!(f, "invalid XML: {error}"),
+            TiledError::Tileset { source, error } => write!(f, "couldn't read tileset '{source}': {error}"),
+            TiledError::MissingAttribute { element, attribute } => {
+                write!(f, "<{element}> is missing the '{attribute}' attribute")
+            }
+            TiledError::UnsupportedEncoding(encoding) => {
+                write!(f, "layer encoding '{encoding}' isn't supported, save the map with CSV layers")
+            }
+            TiledError::LayerSize { layer, expected, found } => {
+                write!(f, "layer '{layer}' has {found} tiles, expected {expected}")
+            }
+            TiledError::InvalidGid(value) => write!(f, "'{value}' isn't a tile id"),
+            TiledError::UnknownGid(gid) => write!(f, "tile {gid} has no 'sprite' property in its tileset"),
+            TiledError::UnknownTileType(name) => write!(f, "'{name}' isn't a TileType"),
+            TiledError::InvalidRowOffset(value) => write!(f, "'{value}' isn't a row offset"),
+        }
+    }
+}
+
+impl std::error::Error for TiledError {}
+
+impl From<std::io::Error> for TiledError {
+    fn from(error: std::io::Error) -> Self {
+        TiledError::Io(error)
+    }
+}
+
+impl From<roxmltree::Error> for TiledError {
+    fn from(error: roxmltree::Error) -> Self {
+        TiledError::Xml(error)
+    }
+}
+
+/// Tileset tiles by local id, plus the gid the tileset starts at.
+struct Tileset {
+    first_gid: u32,
+    tiles: HashMap<u32, TiledTile>,
+}
+
+fn attribute<'a>(node: roxmltree::Node<'a, '_>, element: &'static str, attribute: &'static str) -> Result<&'a str, TiledError> {
+    node.attribute(attribute)
+        .ok_or(TiledError::MissingAttribute { element, attribute })
+}
+
+fn parse_u32(value: &str) -> Result<u32, TiledError> {
+    value.trim().parse().map_err(|_| TiledError::InvalidGid(value.trim().to_string()))
+}
+
+/// Read the `sprite`/`tile_type`/`row_offset` properties of every tile in a `<tileset>` element.
+fn parse_tileset_tiles(tileset: roxmltree::Node) -> Result<HashMap<u32, TiledTile>, TiledError> {
+    let mut tiles = HashMap::new();
+
+    for tile in tileset.children().filter(|node| node.has_tag_name("tile")) {
+        let id = parse_u32(attribute(tile, "tile", "id")?)?;
+
+        let properties = tile
+            .children()
+            .filter(|node| node.has_tag_name("properties"))
+            .flat_map(|node| node.children().filter(|node| node.has_tag_name("property")));
+
+        let mut sprite = None;
+        let mut tile_type = None;
+        let mut row_offset = 0;
+        for property in properties {
+            let value = property.attribute("value").unwrap_or_default();
+            match property.attribute("name") {
+                Some("sprite") => sprite = Some(value.to_string()),
+                Some("tile_type") => {
+                    tile_type = Some(
+                        TileType::from_name(value)
+                            .ok_or_else(|| TiledError::UnknownTileType(value.to_string()))?,
+                    );
+                }
+                Some("row_offset") => {
+                    row_offset = value
+                        .trim()
+                        .parse()
+                        .map_err(|_| TiledError::InvalidRowOffset(value.to_string()))?;
+                }
+                _ => {}
+            }
+        }
+
+        // Tiles without a sprite are decoration in Tiled only
+        if let Some(sprite) = sprite {
+            tiles.insert(id, TiledTile { sprite, tile_type, row_offset });
+        }
+    }
+
+    Ok(tiles)
+}
+
+#[derive(Default, TypePath)]
+pub struct TiledMapLoader;
+
+impl AssetLoader for TiledMapLoader {
+    type Asset = TiledMap;
+    type Settings = ();
+    type Error = TiledError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes).await?;
+        let text = String::from_utf8_lossy(&bytes);
+        let document = roxmltree::Document::parse(&text)?;
+        let map = document.root_element();
+
+        let width = parse_u32(attribute(map, "map", "width")?)?;
+        let height = parse_u32(attribute(map, "map", "height")?)?;
+
+        // Tilesets, either inline or in external .tsx files next to the map
+        let map_dir = load_context.path().parent().map(Path::to_path_buf).unwrap_or_default();
+        let mut tilesets = Vec::new();
+        for node in map.children().filter(|node| node.has_tag_name("tileset")) {
+            let first_gid = parse_u32(attribute(node, "tileset", "firstgid")?)?;
+
+            let tiles = match node.attribute("source") {
+                Some(source) => {
+                    let tsx_bytes = load_context
+                        .read_asset_bytes(map_dir.join(source))
+                        .await
+                        .map_err(|error| TiledError::Tileset { source: source.to_string(), error })?;
+                    let tsx_text = String::from_utf8_lossy(&tsx_bytes);
+                    let tsx = roxmltree::Document::parse(&tsx_text)?;
+                    parse_tileset_tiles(tsx.root_element())?
+                }
+                None => parse_tileset_tiles(node)?,
+            };
+            tilesets.push(Tileset { first_gid, tiles });
+        }
+        tilesets.sort_by_key(|tileset| tileset.first_gid);
+
+        let lookup = |gid: u32| -> Result<Option<TiledTile>, TiledError> {
+            let gid = gid & GID_FLAGS_MASK;
+            if gid == 0 {
+                return Ok(None); // Empty cell
+            }
+            let tileset = tilesets
+                .iter()
+                .rev()
+                .find(|tileset| tileset.first_gid <= gid)
+                .ok_or(TiledError::UnknownGid(gid))?;
+            let tile = tileset
+                .tiles
+                .get(&(gid - tileset.first_gid))
+                .ok_or(TiledError::UnknownGid(gid))?;
+            Ok(Some(tile.clone()))
+        };
+
+        let mut layers = Vec::new();
+        for (index, layer) in map.children().filter(|node| node.has_tag_name("layer")).enumerate() {
+            let name = layer.attribute("name").unwrap_or_default().to_string();
+            let z = layer
+                .children()
+                .filter(|node| node.has_tag_name("properties"))
+                .flat_map(|node| node.children().filter(|node| node.has_tag_name("property")))
+                .find(|property| property.attribute("name") == Some("z"))
+                .and_then(|property| property.attribute("value"))
+                .map(parse_u32)
+                .transpose()?
+                .unwrap_or(index as u32);
+            let Some(data) = layer.children().find(|node| node.has_tag_name("data")) else {
+                return Err(TiledError::MissingAttribute { element: "layer", attribute: "data" });
+            };
+
+            let encoding = data.attribute("encoding").unwrap_or("xml");
+            if encoding != "csv" {
+                return Err(TiledError::UnsupportedEncoding(encoding.to_string()));
+            }
+
+            let gids: Vec<u32> = data
+                .text()
+                .unwrap_or_default()
+                .split(',')
+                .filter(|value| !value.trim().is_empty())
+                .map(parse_u32)
+                .collect::<Result<_, _>>()?;
+
+            let expected = (width * height) as usize;
+            if gids.len() != expected {
+                return Err(TiledError::LayerSize { layer: name, expected, found: gids.len() });
+            }
+
+            // Tiled rows go top to bottom, our grid goes bottom to top
+            let mut tiles = Vec::with_capacity(expected);
+            for y in 0..height {
+                let row = (height - 1 - y) * width;
+                for x in 0..width {
+                    tiles.push(lookup(gids[(row + x) as usize])?);
+                }
+            }
+
+            layers.push(TiledLayer { name, z, tiles });
+        }
+
+        Ok(TiledMap { width, height, layers })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmx"]
+    }
+}
+
+#[derive(Resource)]
+pub struct TiledMapResource {
+    pub handle: Handle<TiledMap>,
+}
+
+/// Start loading the Tiled level, if one is configured instead of WFC.
+pub fn load_tiled_map(mut commands: Commands, asset_server: Res<AssetServer>) {
+    let Some(path) = crate::config::map::TILED_MAP else {
+        return;
+    };
+
+    commands.insert_resource(TiledMapResource {
+        handle: asset_server.load(path),
+    });
+}
+
+/// Spawn hooks of the generator's assets, by sprite name, so an imported tree
+/// top fades like a generated one. The first asset using a sprite wins.
+fn rule_hooks() -> HashMap<&'static str, Vec<TileHook>> {
+    let mut hooks = HashMap::new();
+    for asset in build_rule_set().assets.iter().flatten() {
+        hooks.entry(asset.sprite_name()).or_insert_with(|| asset.hooks().to_vec());
+    }
+    hooks
+}
+
+/// Spawn the Tiled level the same way `NodesSpawner` spawns generated nodes:
+/// children of a map entity at the grid origin, built from the same
+/// `TileBundle`s and with the same Y-based Z offset (taken from the row of the
+/// node a tile belongs to), so collision, depth sorting, fading and animation
+/// can't tell them apart.
+pub fn spawn_tiled_map(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
+    tilemap_res: Res<TilemapResource>,
+    tilemaps: Res<Assets<TilemapDefinition>>,
+    tiled_res: Res<TiledMapResource>,
+    tiled_maps: Res<Assets<TiledMap>>,
+) {
+    let (Some(tilemap), Some(tiled)) = (tilemaps.get(&tilemap_res.handle), tiled_maps.get(&tiled_res.handle)) else {
+        error!("Tiled map or tilemap atlas isn't loaded, can't spawn the map");
+        return;
+    };
+
+    if tiled.width != GRID_X || tiled.height != GRID_Y {
+        warn!(
+            "Tiled map is {}x{}, the window and collision expect {}x{}",
+            tiled.width, tiled.height, GRID_X, GRID_Y
+        );
+    }
+
+    let tilemap_handles = prepare_tilemap_handles(tilemap, &asset_server, &mut atlas_layouts, ASSETS_PATH, TILEMAP_FILE);
+    let rule_hooks = rule_hooks();
+
+    let map_entity = commands
+        .spawn((
+            MapRoot,
+            Transform::from_translation(Vec3::new(
+                -TILE_SIZE * GRID_X as f32 / 2.,
+                -TILE_SIZE * GRID_Y as f32 / 2.,
+                0.,
+            )),
+            Visibility::default(),
+        ))
+        .id();
+
+    for (index, layer) in tiled.layers.iter().enumerate() {
+        let z = layer.z;
+        let context = format!("Tiled layer '{}'", layer.name);
+        for y in 0..tiled.height {
+            for x in 0..tiled.width {
+                let Some(tile) = tiled.tile(index, x, y) else {
+                    continue;
+                };
+                let hooks = rule_hooks.get(tile.sprite.as_str()).map(Vec::as_slice).unwrap_or_default();
+                let Some(bundle) = tile_bundle(tilemap, &tilemap_handles, &tile.sprite, tile.tile_type, hooks, &context)
+                else {
+                    continue;
+                };
+
+                // Same placement as NodesSpawner with z_offset_from_y
+                let depth_row = tile.depth_row(y) as f32;
+                let translation = Vec3::new(
+                    NODE_SIZE.x * (x as f32 + 0.5),
+                    NODE_SIZE.y * (y as f32 + 0.5),
+                    NODE_SIZE.z * (z as f32 + 0.5) + NODE_SIZE.z * (1. - depth_row / tiled.height as f32),
+                );
+
+                let mut tile_entity = commands.spawn_empty();
+                bundle.insert_bundle(&mut tile_entity, translation, ASSETS_SCALE, ModelRotation::Rot0);
+                let tile_entity = tile_entity.id();
+
+                commands.entity(map_entity).add_child(tile_entity);
+            }
+        }
+    }
+
+    info!("Spawned Tiled map with {} layers", tiled.layers.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy::asset::{LoadState, ron};
+
+    use super::*;
+    use crate::map::export::world_to_tmx;
+
+    fn tilemap() -> TilemapDefinition {
+        ron::from_str(
+            r#"(
+                tile_width: 32,
+                tile_height: 32,
+                atlas_width: 96,
+                atlas_height: 32,
+                sprites: [
+                    (name: "dirt", pixel_x: 0, pixel_y: 0),
+                    (name: "tree_trunk", pixel_x: 32, pixel_y: 0),
+                    (name: "tree_top", pixel_x: 64, pixel_y: 0),
+                ],
+                animations: [],
+            )"#,
+        )
+        .unwrap()
+    }
+
+    fn tile(sprite: &str, tile_type: Option<TileType>, row_offset: u32) -> TiledTile {
+        TiledTile { sprite: sprite.to_string(), tile_type, row_offset }
+    }
+
+    /// Load `tmx` through `TiledMapLoader`, from a file in a scratch asset folder.
+    fn load_tmx(name: &str, tmx: &str) -> TiledMap {
+        let dir = std::env::temp_dir().join(format!("chapter4_{name}_{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("map.tmx"), tmx).unwrap();
+
+        let mut app = App::new();
+        app.add_plugins((
+            MinimalPlugins,
+            AssetPlugin { file_path: dir.to_string_lossy().into_owned(), ..default() },
+        ))
+        .init_asset::<TiledMap>()
+        .register_asset_loader(TiledMapLoader);
+        let handle: Handle<TiledMap> = app.world().resource::<AssetServer>().load("map.tmx");
+
+        for _ in 0..1000 {
+            app.update();
+            if let Some(map) = app.world().resource::<Assets<TiledMap>>().get(&handle) {
+                let map = map.clone();
+                let _ = std::fs::remove_dir_all(&dir);
+                return map;
+            }
+            if let LoadState::Failed(error) = app.world().resource::<AssetServer>().load_state(&handle) {
+                panic!("{error}");
+            }
+            std::thread::sleep(Duration::from_millis(5));
+        }
+        panic!("map.tmx didn't load");
+    }
+
+    #[test]
+    fn exported_maps_load_back_in_place() {
+        let mut world = TiledMap::empty(3, 2);
+        for y in 0..2 {
+            for x in 0..3 {
+                world.place(x, y, 0, tile("dirt", Some(TileType::Dirt), 0));
+            }
+        }
+        world.place(1, 0, 1, tile("tree_trunk", Some(TileType::Tree), 0));
+        // The top sits one row up but belongs to the trunk's node
+        world.place(1, 1, 1, tile("tree_top", None, 1));
+
+        let loaded = load_tmx("roundtrip", &world_to_tmx(&world, &tilemap(), "tilemap.png"));
+
+        assert_eq!((loaded.width, loaded.height), (3, 2));
+        assert_eq!(loaded.layers.iter().map(|layer| layer.z).collect::<Vec<_>>(), vec![0, 1]);
+        for (index, layer) in world.layers.iter().enumerate() {
+            for y in 0..2 {
+                for x in 0..3 {
+                    let expected = world.tile(index, x, y).map(|tile| (&tile.sprite, tile.tile_type, tile.row_offset));
+                    let found = loaded.tile(index, x, y).map(|tile| (&tile.sprite, tile.tile_type, tile.row_offset));
+                    assert_eq!(found, expected, "layer {} at ({x}, {y})", layer.name);
+                }
+            }
+        }
+
+        let top = loaded.tile(1, 1, 1).unwrap();
+        assert_eq!(top.depth_row(1), 0);
+    }
+}
@@ -1,17 +1,164 @@
+use std::collections::HashMap;
+use std::fmt;
+
 use bevy::math::{URect, UVec2};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TilemapSprite {
-    pub name: &'static str,
+    pub name: String,
     pub pixel_x: u32,
     pub pixel_y: u32,
+    /// sRGBA multiplied into the sprite, so one drawing can serve several
+    /// tiles (e.g. `sand` and `snow` are tinted `dirt` until they get art)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tint: Option<[f32; 4]>,
+}
+
+/// A sprite that cycles through several atlas frames (water, shore, foliage).
+/// Every tile spawned with `sprite` animates through `frames` in order.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TilemapAnimation {
+    /// Sprite name used in the rules
+    pub sprite: String,
+    /// Sprite names of each frame, usually starting with `sprite` itself
+    pub frames: Vec<String>,
+    /// Seconds per frame
+    pub frame_time: f32,
+}
+
+/// The atlas exactly as written in the `.atlas.ron` file, before validation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct TilemapData {
+    tile_width: u32,
+    tile_height: u32,
+    atlas_width: u32,
+    atlas_height: u32,
+    sprites: Vec<TilemapSprite>,
+    #[serde(default)]
+    animations: Vec<TilemapAnimation>,
 }
 
+/// Describes where each named sprite sits in the tilemap image.
+/// Loaded from `tile_layers/tilemap.atlas.ron`; a file that fails validation
+/// fails to load instead of panicking later.
+#[derive(Asset, TypePath, Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "TilemapData", into = "TilemapData")]
 pub struct TilemapDefinition {
     pub tile_width: u32,
     pub tile_height: u32,
     pub atlas_width: u32,
     pub atlas_height: u32,
-    pub sprites: &'static [TilemapSprite],
+    pub sprites: Vec<TilemapSprite>,
+    pub animations: Vec<TilemapAnimation>,
+    /// Sprite name -> atlas index
+    sprite_indices: HashMap<String, usize>,
+}
+
+/// Problems found while validating an atlas description.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TilemapError {
+    EmptyTileSize,
+    DuplicateSprite(String),
+    OutOfBounds { name: String, rect: URect, atlas_size: UVec2 },
+    UnknownFrame { animation: String, frame: String },
+    InvalidFrameTime(String),
+    ImageTooSmall { declared: UVec2, found: UVec2 },
+}
+
+impl fmt::Display for TilemapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TilemapError::EmptyTileSize => write!(f, "tile size must be non-zero"),
+            TilemapError::DuplicateSprite(name) => write!(f, "sprite '{name}' is defined twice"),
+            TilemapError::OutOfBounds { name, rect, atlas_size } => write!(
+                f,
+                "sprite '{name}' ({:?}..{:?}) lies outside the {}x{} atlas",
+                rect.min, rect.max, atlas_size.x, atlas_size.y
+            ),
+            TilemapError::UnknownFrame { animation, frame } => {
+                write!(f, "animation '{animation}' uses unknown sprite '{frame}'")
+            }
+            TilemapError::InvalidFrameTime(animation) => {
+                write!(f, "animation '{animation}' needs a positive frame_time")
+            }
+            TilemapError::ImageTooSmall { declared, found } => write!(
+                f,
+                "the atlas image is {}x{}, smaller than the declared {}x{}",
+                found.x, found.y, declared.x, declared.y
+            ),
+        }
+    }
+}
+
+impl std::error::Error for TilemapError {}
+
+impl TryFrom<TilemapData> for TilemapDefinition {
+    type Error = TilemapError;
+
+    fn try_from(data: TilemapData) -> Result<Self, Self::Error> {
+        if data.tile_width == 0 || data.tile_height == 0 {
+            return Err(TilemapError::EmptyTileSize);
+        }
+
+        let definition = TilemapDefinition {
+            tile_width: data.tile_width,
+            tile_height: data.tile_height,
+            atlas_width: data.atlas_width,
+            atlas_height: data.atlas_height,
+            sprites: data.sprites,
+            animations: data.animations,
+            sprite_indices: HashMap::new(),
+        };
+
+        let mut sprite_indices = HashMap::with_capacity(definition.sprites.len());
+        let atlas = URect::from_corners(UVec2::ZERO, definition.atlas_size());
+
+        for (index, sprite) in definition.sprites.iter().enumerate() {
+            let rect = definition.sprite_rect(index);
+            if atlas.union(rect) != atlas {
+                return Err(TilemapError::OutOfBounds {
+                    name: sprite.name.clone(),
+                    rect,
+                    atlas_size: definition.atlas_size(),
+                });
+            }
+
+            if sprite_indices.insert(sprite.name.clone(), index).is_some() {
+                return Err(TilemapError::DuplicateSprite(sprite.name.clone()));
+            }
+        }
+
+        for animation in &definition.animations {
+            if animation.frame_time <= 0.0 {
+                return Err(TilemapError::InvalidFrameTime(animation.sprite.clone()));
+            }
+            for frame in &animation.frames {
+                if !sprite_indices.contains_key(frame) {
+                    return Err(TilemapError::UnknownFrame {
+                        animation: animation.sprite.clone(),
+                        frame: frame.clone(),
+                    });
+                }
+            }
+        }
+
+        Ok(TilemapDefinition { sprite_indices, ..definition })
+    }
+}
+
+impl From<TilemapDefinition> for TilemapData {
+    fn from(definition: TilemapDefinition) -> Self {
+        TilemapData {
+            tile_width: definition.tile_width,
+            tile_height: definition.tile_height,
+            atlas_width: definition.atlas_width,
+            atlas_height: definition.atlas_height,
+            sprites: definition.sprites,
+            animations: definition.animations,
+        }
+    }
 }
 
 impl TilemapDefinition {
@@ -24,7 +171,18 @@ impl TilemapDefinition {
     }
 
     pub fn sprite_index(&self, name: &str) -> Option<usize> {
-        self.sprites.iter().position(|sprite| sprite.name == name)
+        self.sprite_indices.get(name).copied()
+    }
+
+    pub fn animation(&self, sprite: &str) -> Option<&TilemapAnimation> {
+        self.animations.iter().find(|animation| animation.sprite == sprite)
+    }
+
+    pub fn sprite_tint(&self, index: usize) -> Color {
+        match self.sprites[index].tint {
+            Some([r, g, b, a]) => Color::srgba(r, g, b, a),
+            None => Color::WHITE,
+        }
     }
 
     pub fn sprite_rect(&self, index: usize) -> URect {
@@ -33,317 +191,3 @@ impl TilemapDefinition {
         URect::from_corners(min, min + self.tile_size())
     }
 }
-
-pub const TILEMAP: TilemapDefinition = TilemapDefinition {
-    tile_width: 32,
-    tile_height: 32,
-    atlas_width: 256,
-    atlas_height: 320,
-    sprites: &[
-          TilemapSprite {
-            name: "dirt",
-            pixel_x: 128,
-            pixel_y: 0,
-        },
-        TilemapSprite {
-            name: "green_grass",
-            pixel_x: 160,
-            pixel_y: 0,
-        },
-        TilemapSprite {
-            name: "green_grass_corner_in_tl",
-            pixel_x: 192,
-            pixel_y: 0,
-        },
-        TilemapSprite {
-            name: "green_grass_corner_in_tr",
-            pixel_x: 224,
-            pixel_y: 0,
-        },
-        TilemapSprite {
-            name: "green_grass_corner_in_bl",
-            pixel_x: 192,
-            pixel_y: 32,
-        },
-        TilemapSprite {
-            name: "green_grass_corner_in_br",
-            pixel_x: 224,
-            pixel_y: 32,
-        },
-        TilemapSprite {
-            name: "green_grass_corner_out_tl",
-            pixel_x: 0,
-            pixel_y: 64,
-        },
-        TilemapSprite {
-            name: "green_grass_corner_out_tr",
-            pixel_x: 32,
-            pixel_y: 64,
-        },
-        TilemapSprite {
-            name: "green_grass_corner_out_bl",
-            pixel_x: 0,
-            pixel_y: 96,
-        },
-        TilemapSprite {
-            name: "green_grass_corner_out_br",
-            pixel_x: 32,
-            pixel_y: 96,
-        },
-        TilemapSprite {
-            name: "green_grass_side_t",
-            pixel_x: 64,
-            pixel_y: 64,
-        },
-        TilemapSprite {
-            name: "green_grass_side_r",
-            pixel_x: 96,
-            pixel_y: 64,
-        },
-        TilemapSprite {
-            name: "green_grass_side_l",
-            pixel_x: 64,
-            pixel_y: 96,
-        },
-        TilemapSprite {
-            name: "green_grass_side_b",
-            pixel_x: 96,
-            pixel_y: 96,
-        },
-        TilemapSprite {
-            name: "yellow_grass",
-            pixel_x: 0,
-            pixel_y: 256,
-        },
-        TilemapSprite {
-            name: "yellow_grass_corner_in_tl",
-            pixel_x: 32,
-            pixel_y: 256,
-        },
-        TilemapSprite {
-            name: "yellow_grass_corner_in_tr",
-            pixel_x: 64,
-            pixel_y: 256,
-        },
-        TilemapSprite {
-            name: "yellow_grass_corner_in_bl",
-            pixel_x: 32,
-            pixel_y: 288,
-        },
-        TilemapSprite {
-            name: "yellow_grass_corner_in_br",
-            pixel_x: 64,
-            pixel_y: 288,
-        },
-        TilemapSprite {
-            name: "yellow_grass_corner_out_tl",
-            pixel_x: 96,
-            pixel_y: 256,
-        },
-        TilemapSprite {
-            name: "yellow_grass_corner_out_tr",
-            pixel_x: 128,
-            pixel_y: 256,
-        },
-        TilemapSprite {
-            name: "yellow_grass_corner_out_bl",
-            pixel_x: 96,
-            pixel_y: 288,
-        },
-        TilemapSprite {
-            name: "yellow_grass_corner_out_br",
-            pixel_x: 128,
-            pixel_y: 288,
-        },
-        TilemapSprite {
-            name: "yellow_grass_side_t",
-            pixel_x: 160,
-            pixel_y: 256,
-        },
-        TilemapSprite {
-            name: "yellow_grass_side_r",
-            pixel_x: 192,
-            pixel_y: 256,
-        },
-        TilemapSprite {
-            name: "yellow_grass_side_l",
-            pixel_x: 160,
-            pixel_y: 288,
-        },
-        TilemapSprite {
-            name: "yellow_grass_side_b",
-            pixel_x: 192,
-            pixel_y: 288,
-        },
-        TilemapSprite {
-            name: "water",
-            pixel_x: 32,
-            pixel_y: 192,
-        },
-        TilemapSprite {
-            name: "water_corner_in_tl",
-            pixel_x: 64,
-            pixel_y: 192,
-        },
-        TilemapSprite {
-            name: "water_corner_in_tr",
-            pixel_x: 96,
-            pixel_y: 192,
-        },
-        TilemapSprite {
-            name: "water_corner_in_bl",
-            pixel_x: 64,
-            pixel_y: 224,
-        },
-        TilemapSprite {
-            name: "water_corner_in_br",
-            pixel_x: 96,
-            pixel_y: 224,
-        },
-        TilemapSprite {
-            name: "water_corner_out_tl",
-            pixel_x: 128,
-            pixel_y: 192,
-        },
-        TilemapSprite {
-            name: "water_corner_out_tr",
-            pixel_x: 160,
-            pixel_y: 192,
-        },
-        TilemapSprite {
-            name: "water_corner_out_bl",
-            pixel_x: 128,
-            pixel_y: 224,
-        },
-        TilemapSprite {
-            name: "water_corner_out_br",
-            pixel_x: 160,
-            pixel_y: 224,
-        },
-        TilemapSprite {
-            name: "water_side_t",
-            pixel_x: 192,
-            pixel_y: 192,
-        },
-        TilemapSprite {
-            name: "water_side_r",
-            pixel_x: 224,
-            pixel_y: 192,
-        },
-        TilemapSprite {
-            name: "water_side_l",
-            pixel_x: 192,
-            pixel_y: 224,
-        },
-        TilemapSprite {
-            name: "water_side_b",
-            pixel_x: 224,
-            pixel_y: 224,
-        },
-        TilemapSprite {
-            name: "big_tree_1_tl",
-            pixel_x: 0,
-            pixel_y: 0,
-        },
-        TilemapSprite {
-            name: "big_tree_1_tr",
-            pixel_x: 32,
-            pixel_y: 0,
-        },
-        TilemapSprite {
-            name: "big_tree_1_bl",
-            pixel_x: 0,
-            pixel_y: 32,
-        },
-        TilemapSprite {
-            name: "big_tree_1_br",
-            pixel_x: 32,
-            pixel_y: 32,
-        },
-        TilemapSprite {
-            name: "big_tree_2_tl",
-            pixel_x: 64,
-            pixel_y: 0,
-        },
-        TilemapSprite {
-            name: "big_tree_2_tr",
-            pixel_x: 96,
-            pixel_y: 0,
-        },
-        TilemapSprite {
-            name: "big_tree_2_bl",
-            pixel_x: 64,
-            pixel_y: 32,
-        },
-        TilemapSprite {
-            name: "big_tree_2_br",
-            pixel_x: 96,
-            pixel_y: 32,
-        },
-        TilemapSprite {
-            name: "plant_1",
-            pixel_x: 128,
-            pixel_y: 64,
-        },
-        TilemapSprite {
-            name: "plant_2",
-            pixel_x: 160,
-            pixel_y: 64,
-        },
-        TilemapSprite {
-            name: "plant_3",
-            pixel_x: 192,
-            pixel_y: 64,
-        },
-        TilemapSprite {
-            name: "plant_4",
-            pixel_x: 224,
-            pixel_y: 64,
-        },
-        TilemapSprite {
-            name: "rock_1",
-            pixel_x: 0,
-            pixel_y: 128,
-        },
-        TilemapSprite {
-            name: "rock_2",
-            pixel_x: 32,
-            pixel_y: 128,
-        },
-        TilemapSprite {
-            name: "rock_3",
-            pixel_x: 64,
-            pixel_y: 128,
-        },
-        TilemapSprite {
-            name: "rock_4",
-            pixel_x: 96,
-            pixel_y: 128,
-        },
-        TilemapSprite {
-            name: "small_tree_top",
-            pixel_x: 128,
-            pixel_y: 128,
-        },
-        TilemapSprite {
-            name: "small_tree_bottom",
-            pixel_x: 128,
-            pixel_y: 160,
-        },
-        TilemapSprite {
-            name: "tree_stump_1",
-            pixel_x: 192,
-            pixel_y: 128,
-        },
-        TilemapSprite {
-            name: "tree_stump_2",
-            pixel_x: 224,
-            pixel_y: 128,
-        },
-        TilemapSprite {
-            name: "tree_stump_3",
-            pixel_x: 0,
-            pixel_y: 192,
-        },
-    ]
-};
\ No newline at end of file
@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+
+use crate::characters::input::Player;
+use crate::collision::{TriggerEvent, TriggerZone};
+
+/// Shows the name of the trigger zone the player is in ("Lakeside"...).
+#[derive(Component, Default)]
+pub struct RegionLabel {
+    /// Zones the player is inside, the most recently entered last
+    zones: Vec<Entity>,
+}
+
+pub fn spawn_region_label(mut commands: Commands) {
+    commands.spawn((
+        RegionLabel::default(),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(12.0),
+            left: Val::Px(12.0),
+            padding: UiRect::axes(Val::Px(10.0), Val::Px(4.0)),
+            ..default()
+        },
+        BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.5)),
+        Text::new(""),
+        TextFont {
+            font_size: 24.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Visibility::Hidden,
+    ));
+}
+
+/// Follow the player's zone enter/exit messages; the label shows the newest zone.
+pub fn update_region_label(
+    mut trigger_reader: MessageReader<TriggerEvent>,
+    player_query: Query<(), With<Player>>,
+    zone_query: Query<&TriggerZone>,
+    mut label_query: Query<(&mut RegionLabel, &mut Text, &mut Visibility)>,
+) {
+    let Ok((mut label, mut text, mut visibility)) = label_query.single_mut() else {
+        return;
+    };
+
+    let mut changed = false;
+    for event in trigger_reader.read() {
+        match *event {
+            TriggerEvent::Enter { zone, entity } if player_query.contains(entity) => {
+                label.zones.retain(|&other| other != zone);
+                label.zones.push(zone);
+                changed = true;
+            }
+            TriggerEvent::Exit { zone, entity } if player_query.contains(entity) => {
+                label.zones.retain(|&other| other != zone);
+                changed = true;
+            }
+            _ => {}
+        }
+    }
+    if !changed {
+        return;
+    }
+
+    // Zones despawned since they were entered are skipped
+    match label.zones.iter().rev().find_map(|&zone| zone_query.get(zone).ok()) {
+        Some(zone) => {
+            **text = zone.name.clone();
+            *visibility = Visibility::Inherited;
+        }
+        None => {
+            text.clear();
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
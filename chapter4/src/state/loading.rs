@@ -6,6 +6,34 @@ pub struct LoadingScreen;
 #[derive(Component)]
 pub struct LoadingText;
 
+/// What the game is busy with before it can start.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoadingStage {
+    /// Assets, and Tiled levels (they spawn in one go)
+    #[default]
+    Assets,
+    /// The WFC is running in the background
+    World,
+    /// Tiles are spawned, the collision map is being built
+    Collision,
+    /// An asset didn't load or the world couldn't be generated, the game can't start
+    Failed,
+}
+
+/// Shown on the loading screen; the map plugin updates it while the world builds.
+#[derive(Resource, Debug, Default)]
+pub struct LoadingProgress {
+    pub stage: LoadingStage,
+    /// Completion of the current stage, 0..1
+    pub fraction: f32,
+    /// Why loading failed, when there's more to say than the stage
+    pub error: Option<String>,
+}
+
+/// Inserted once every asset is loaded, which starts the world's construction.
+#[derive(Resource)]
+pub struct AssetsLoaded;
+
 pub fn spawn_loading_screen(mut commands: Commands) {
     commands.spawn((
         LoadingScreen,
@@ -34,11 +62,17 @@ pub fn spawn_loading_screen(mut commands: Commands) {
 
 pub fn animate_loading(
     time: Res<Time>,
+    progress: Res<LoadingProgress>,
     mut query: Query<&mut Text, With<LoadingText>>,
 ) {
     for mut text in query.iter_mut() {
-        let dots = (time.elapsed_secs() * 2.0) as usize % 4;
-        **text = format!("Loading{}", ".".repeat(dots));
+        let dots = ".".repeat((time.elapsed_secs() * 2.0) as usize % 4);
+        **text = match progress.stage {
+            LoadingStage::Assets => format!("Loading{dots}"),
+            LoadingStage::World => format!("Generating world {:.0}%{dots}", progress.fraction * 100.0),
+            LoadingStage::Collision => format!("Building collision{dots}"),
+            LoadingStage::Failed => progress.error.clone().unwrap_or_else(|| "World generation failed".to_string()),
+        };
     }
 }
 
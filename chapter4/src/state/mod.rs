@@ -1,12 +1,19 @@
 mod game_state;
+mod hud;
 mod loading;
 mod pause;
 
+use bevy::asset::LoadState;
 use bevy::prelude::*;
 use crate::characters::spawn::CharactersListResource;
 use crate::characters::config::CharactersList;
+use crate::collision::CollisionMapBuilt;
+use crate::map::generate::TilemapResource;
+use crate::map::tilemap::TilemapDefinition;
+use crate::map::tiled::{TiledMap, TiledMapResource};
 
 pub use game_state::GameState;
+pub use loading::{AssetsLoaded, LoadingProgress, LoadingStage};
 
 pub struct StatePlugin;
 
@@ -14,17 +21,21 @@ impl Plugin for StatePlugin {
     fn build(&self, app: &mut App) {
         app
             .init_state::<GameState>()
+            .init_resource::<LoadingProgress>()
             
             // Loading state systems
             .add_systems(OnEnter(GameState::Loading), loading::spawn_loading_screen)
             .add_systems(Update, (
-                check_assets_loaded,
+                (check_assets_failed, check_assets_loaded).run_if(not(resource_exists::<AssetsLoaded>)),
+                check_world_ready,
                 loading::animate_loading,
             ).run_if(in_state(GameState::Loading)))
             .add_systems(OnExit(GameState::Loading), (
                 loading::despawn_loading_screen,
+                hud::spawn_region_label,
                 crate::characters::spawn::initialize_player_character,
             ))
+            .add_systems(Update, hud::update_region_label.run_if(in_state(GameState::Playing)))
                 // Pause state systems
             .add_systems(OnEnter(GameState::Paused), pause::spawn_pause_menu)
             .add_systems(OnExit(GameState::Paused), pause::despawn_pause_menu)
@@ -37,16 +48,62 @@ impl Plugin for StatePlugin {
 }
 
 fn check_assets_loaded(
+    mut commands: Commands,
     characters_list_res: Option<Res<CharactersListResource>>,
     characters_lists: Res<Assets<CharactersList>>,
-    mut next_state: ResMut<NextState<GameState>>,
+    tilemap_res: Option<Res<TilemapResource>>,
+    tilemaps: Res<Assets<TilemapDefinition>>,
+    tiled_res: Option<Res<TiledMapResource>>,
+    tiled_maps: Res<Assets<TiledMap>>,
 ) {
-    let Some(res) = characters_list_res else {
+    let (Some(res), Some(tilemap_res)) = (characters_list_res, tilemap_res) else {
         return;
     };
     
-    if characters_lists.get(&res.handle).is_some() {
-        info!("Assets loaded, transitioning to Playing!");
+    // Only wait for a Tiled level if one is configured
+    let tiled_loaded = tiled_res.is_none_or(|tiled_res| tiled_maps.get(&tiled_res.handle).is_some());
+    
+    if characters_lists.get(&res.handle).is_some() && tilemaps.get(&tilemap_res.handle).is_some() && tiled_loaded {
+        info!("Assets loaded, building the world");
+        commands.insert_resource(AssetsLoaded);
+    }
+}
+
+/// An asset that fails to load (e.g. an atlas that fails validation) never
+/// shows up in its `Assets`, so stop waiting for it and show why.
+fn check_assets_failed(
+    asset_server: Res<AssetServer>,
+    mut progress: ResMut<LoadingProgress>,
+    characters_list_res: Option<Res<CharactersListResource>>,
+    tilemap_res: Option<Res<TilemapResource>>,
+    tiled_res: Option<Res<TiledMapResource>>,
+) {
+    if progress.stage == LoadingStage::Failed {
+        return;
+    }
+
+    let handles = [
+        characters_list_res.map(|res| ("characters list", res.handle.id().untyped())),
+        tilemap_res.map(|res| ("tilemap atlas", res.handle.id().untyped())),
+        tiled_res.map(|res| ("Tiled map", res.handle.id().untyped())),
+    ];
+    for (name, handle) in handles.into_iter().flatten() {
+        if let LoadState::Failed(err) = asset_server.load_state(handle) {
+            error!("Couldn't load the {name}: {err}");
+            progress.stage = LoadingStage::Failed;
+            progress.error = Some(format!("Couldn't load the {name}: {err}"));
+            return;
+        }
+    }
+}
+
+/// Start playing once the world's tiles and collision map exist.
+fn check_world_ready(
+    built: Res<CollisionMapBuilt>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if built.0 {
+        info!("World ready, transitioning to Playing!");
         next_state.set(GameState::Playing);
     }
 }